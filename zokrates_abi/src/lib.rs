@@ -17,6 +17,7 @@ impl<T: From<usize>> Encode<T> for Inputs<T> {
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
+use zokrates_core::typed_absy::types::Signature;
 use zokrates_core::typed_absy::Type;
 
 use zokrates_field::field::Field;
@@ -304,6 +305,66 @@ pub fn parse_strict<T: Field>(s: &str, types: Vec<Type>) -> Result<CheckedValues
     Ok(CheckedValues(checked))
 }
 
+/// Parses the arguments to a program with the given signature, either as an array with one value
+/// per parameter, or as an object keyed by parameter name if the signature has parameter names
+pub fn parse_strict_signature<T: Field>(
+    s: &str,
+    signature: &Signature,
+) -> Result<CheckedValues<T>, Error> {
+    let json_values: serde_json::Value =
+        serde_json::from_str(s).map_err(|e| Error::Json(e.to_string()))?;
+
+    let values = match json_values {
+        serde_json::Value::Object(mut o) => {
+            if signature.parameters.len() != signature.inputs.len() {
+                return Err(Error::Type(String::from(
+                    "Parameter names are not known for this program, expected an array of values",
+                )));
+            }
+            if o.len() != signature.parameters.len() {
+                return Err(Error::Type(format!(
+                    "Expected {} inputs, found {}",
+                    signature.parameters.len(),
+                    o.len()
+                )));
+            }
+            signature
+                .parameters
+                .iter()
+                .map(|p| {
+                    o.remove(&p.name)
+                        .ok_or_else(|| Error::Type(format!("Parameter `{}` not found", p.name)))
+                        .and_then(|v| Value::try_from(v).map_err(|e| Error::Conversion(e)))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        v => Values::try_from(v).map_err(|e| Error::Conversion(e))?.0,
+    };
+
+    if values.len() != signature.inputs.len() {
+        return Err(Error::Type(format!(
+            "Expected {} inputs, found {}",
+            signature.inputs.len(),
+            values.len()
+        )));
+    }
+
+    let checked = values
+        .into_iter()
+        .zip(signature.inputs.iter())
+        .enumerate()
+        .map(|(i, (v, ty))| {
+            v.check(ty.clone())
+                .map_err(|e| match signature.parameters.get(i) {
+                    Some(p) => format!("Parameter `{}` expected {}: {}", p.name, ty, e),
+                    None => e,
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::Type(e))?;
+    Ok(CheckedValues(checked))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -449,6 +510,100 @@ mod tests {
                 Error::Type("Value `false` doesn't match expected type `field`".into())
             );
         }
+
+        #[test]
+        fn named() {
+            use zokrates_core::typed_absy::types::SignatureParameter;
+
+            let signature = Signature::new()
+                .inputs(vec![Type::FieldElement, Type::array(Type::Boolean, 2)])
+                .parameters(vec![
+                    SignatureParameter {
+                        name: String::from("a"),
+                        public: true,
+                    },
+                    SignatureParameter {
+                        name: String::from("b"),
+                        public: false,
+                    },
+                ]);
+
+            let expected = CheckedValues(vec![
+                CheckedValue::Field(1.into()),
+                CheckedValue::Array(vec![
+                    CheckedValue::Boolean(true),
+                    CheckedValue::Boolean(false),
+                ]),
+            ]);
+
+            let s = r#"{"b": [true, false], "a": "1"}"#;
+            assert_eq!(
+                parse_strict_signature::<FieldPrime>(s, &signature).unwrap(),
+                expected
+            );
+
+            let s = r#"["1", [true, false]]"#;
+            assert_eq!(
+                parse_strict_signature::<FieldPrime>(s, &signature).unwrap(),
+                expected
+            );
+
+            let s = r#"{"a": "1", "c": [true, false]}"#;
+            assert_eq!(
+                parse_strict_signature::<FieldPrime>(s, &signature).unwrap_err(),
+                Error::Type("Parameter `b` not found".into())
+            );
+
+            let s = r#"{"a": "1", "b": [true]}"#;
+            assert_eq!(
+                parse_strict_signature::<FieldPrime>(s, &signature).unwrap_err(),
+                Error::Type(
+                    "Parameter `b` expected bool[2]: Expected array of size 2, found array of size 1"
+                        .into()
+                )
+            );
+
+            let s = r#"{"a": "1"}"#;
+            assert_eq!(
+                parse_strict_signature::<FieldPrime>(
+                    s,
+                    &Signature::new().inputs(vec![Type::FieldElement])
+                )
+                .unwrap_err(),
+                Error::Type(
+                    "Parameter names are not known for this program, expected an array of values"
+                        .into()
+                )
+            );
+        }
+
+        #[test]
+        fn abi_spec() {
+            use zokrates_core::typed_absy::abi::Abi;
+
+            let abi: Abi = serde_json::from_str(
+                r#"{
+                    "inputs": [
+                        {"name": "a", "public": true, "type": "field"},
+                        {"name": "b", "public": false, "type": "array", "size": 2, "components": {"type": "bool"}}
+                    ],
+                    "outputs": []
+                }"#,
+            )
+            .unwrap();
+
+            let s = r#"["1", [true, false]]"#;
+            assert_eq!(
+                parse_strict::<FieldPrime>(s, abi.signature().inputs).unwrap(),
+                CheckedValues(vec![
+                    CheckedValue::Field(1.into()),
+                    CheckedValue::Array(vec![
+                        CheckedValue::Boolean(true),
+                        CheckedValue::Boolean(false)
+                    ])
+                ])
+            );
+        }
     }
 
     mod encode {
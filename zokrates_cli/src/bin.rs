@@ -4,25 +4,31 @@
 // @author Dennis Kuhnert <dennis.kuhnert@campus.tu-berlin.de>
 // @date 2017
 
-use bincode::{deserialize_from, serialize_into, Infinite};
+mod serve;
+
 use clap::{App, AppSettings, Arg, SubCommand};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
 use std::io::{stdin, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::string::String;
 use std::{env, io};
-use zokrates_abi::Encode;
-use zokrates_core::compile::compile;
+use zokrates_abi::{Decode, Encode};
+use zokrates_core::compile::{compile, CompilationArtifacts};
+use zokrates_core::flat_absy::FlatVariable;
 use zokrates_core::ir;
+use zokrates_core::proof_system::bn128::mpc::MPCParameters;
+use zokrates_core::proof_system::bn128::phase1::Phase1;
 use zokrates_core::proof_system::*;
+use zokrates_core::typed_absy::abi::Abi;
+use zokrates_core::typed_absy::types::Signature;
 use zokrates_field::field::{Field, FieldPrime};
 use zokrates_fs_resolver::resolve as fs_resolve;
 
 use zokrates_core::proof_system::bn128::utils::java::{
-    JAVA_LIB_FP, JAVA_LIB_FP2, JAVA_LIB_G1, JAVA_LIB_G1POINT,
-    JAVA_LIB_G2, JAVA_LIB_G2POINT, JAVA_LIB_PAIRING, JAVA_LIB_UTIL
+    java_lib, java_registry, java_test, JavaNames, JAVA_DEFAULT_CLASS, JAVA_DEFAULT_PACKAGE,
 };
 
 #[cfg(feature = "github")]
@@ -58,8 +64,13 @@ fn cli() -> Result<(), String> {
     const VERIFICATION_AVM_CONTRACT_DEFAULT_PATH: &str = "avm-verifier";
 
 
+    const ABI_SPEC_DEFAULT_PATH: &str = "abi.json";
     const WITNESS_DEFAULT_PATH: &str = "witness";
     const JSON_PROOF_PATH: &str = "proof.json";
+    const MPC_PARAMETERS_DEFAULT_PATH: &str = "mpc.params";
+    const R1CS_DEFAULT_PATH: &str = "out.r1cs";
+    const R1CS_JSON_DEFAULT_PATH: &str = "out.r1cs.json";
+    const SERVE_DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
     let default_scheme = env::var("ZOKRATES_PROVING_SCHEME").unwrap_or(String::from("g16"));
     let default_solidity_abi = "v1";
 
@@ -86,12 +97,102 @@ fn cli() -> Result<(), String> {
             .takes_value(true)
             .required(false)
             .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("abi_spec")
+            .short("s")
+            .long("abi_spec")
+            .help("Path of the ABI specification")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(ABI_SPEC_DEFAULT_PATH)
+        ).arg(Arg::with_name("names")
+            .long("names")
+            .help("Name the variables after the source code in the human readable output, e.g. `a[0]` instead of `_42`")
+            .required(false)
+        ).arg(Arg::with_name("locations")
+            .long("locations")
+            .help("Annotate each statement of the human readable output with its location in the source code")
+            .required(false)
         ).arg(Arg::with_name("light")
             .long("light")
             .help("Skip logs and human readable output")
             .required(false)
         )
      )
+    .subcommand(SubCommand::with_name("profile")
+        .about("Attributes the constraints of a compiled program to the modules, functions and source lines they come from")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of compiled code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("folded")
+            .long("folded")
+            .help("Path of a file to write the constraints per call stack to, in the folded format read by flamegraph tools")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        )
+     )
+    .subcommand(SubCommand::with_name("export-r1cs")
+        .about("Exports the constraints of a compiled program in the iden3 '.r1cs' format used by circom and snarkjs")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of compiled code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Path of the output file, `out.r1cs` or `out.r1cs.json` with --json by default")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        ).arg(Arg::with_name("json")
+            .long("json")
+            .help("Write the constraints in JSON instead, with the variable of each wire, for debugging")
+            .required(false)
+        )
+     )
+    .subcommand(SubCommand::with_name("import-r1cs")
+        .about("Imports a constraint system in the iden3 '.r1cs' format as a compiled program, and optionally its witness in the '.wtns' format")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the constraint system")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(true)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Path of the output file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("wtns")
+            .long("wtns")
+            .help("Path of a witness of the constraint system in the '.wtns' format, to convert to a witness of the program")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        ).arg(Arg::with_name("witness")
+            .short("w")
+            .long("witness")
+            .help("Path of the converted witness, used with --wtns")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(WITNESS_DEFAULT_PATH)
+        )
+     )
     .subcommand(SubCommand::with_name("setup")
         .about("Performs a trusted setup for a given constraint system")
         .arg(Arg::with_name("input")
@@ -126,6 +227,12 @@ fn cli() -> Result<(), String> {
             .takes_value(true)
             .required(false)
             .default_value(&default_scheme)
+        ).arg(Arg::with_name("phase1")
+            .long("phase1")
+            .help("Path of a Powers of Tau file to take the circuit-independent parameters from. Only available for G16")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
         ).arg(Arg::with_name("light")
             .long("light")
             .help("Skip logs and human readable output")
@@ -173,9 +280,11 @@ fn cli() -> Result<(), String> {
         .arg(Arg::with_name("input")
             .short("i")
             .long("input")
-            .help("Path of the verifier")
+            .help("Path of the verification key. If given several times, a registry contract is exported which verifies the proofs of each circuit, identified by the index of its key")
             .value_name("FILE")
             .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
             .required(false)
             .default_value(VERIFICATION_KEY_DEFAULT_PATH)
         ).arg(Arg::with_name("output")
@@ -189,11 +298,41 @@ fn cli() -> Result<(), String> {
         ).arg(Arg::with_name("proving-scheme")
             .short("s")
             .long("proving-scheme")
-            .help("Proving scheme to use to export the verifier. Available options are G16 (default), PGHR13 and GM17")
+            .help("Proving scheme to use to export the verifier. Available options are G16 (default), PGHR13 and GM17. Either given once for all verification keys, or once for each of them")
             .value_name("FILE")
             .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
             .required(false)
             .default_value(&default_scheme)
+        ).arg(Arg::with_name("package")
+            .long("package")
+            .help("Java package of the generated classes")
+            .value_name("NAME")
+            .takes_value(true)
+            .required(false)
+            .default_value(JAVA_DEFAULT_PACKAGE)
+        ).arg(Arg::with_name("class")
+            .long("class")
+            .help("Name of the verifier class")
+            .value_name("NAME")
+            .takes_value(true)
+            .required(false)
+            .default_value(JAVA_DEFAULT_CLASS)
+        ).arg(Arg::with_name("prefix")
+            .long("prefix")
+            .help("Prefix added to the names of the helper classes, so that several verifiers can be deployed in one package")
+            .value_name("PREFIX")
+            .takes_value(true)
+            .required(false)
+        ).arg(Arg::with_name("proof")
+            .long("proof")
+            .help("Path of a sample JSON proof, given once for each verification key. If given, a JUnit test is written which deploys the verifier with the AVM tooling and checks that the proof verifies, and fails once tampered with")
+            .value_name("FILE")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
         )
     )
     .subcommand(SubCommand::with_name("compute-witness")
@@ -221,6 +360,14 @@ fn cli() -> Result<(), String> {
             .takes_value(true)
             .multiple(true) // allows multiple values
             .required(false)
+        ).arg(Arg::with_name("abi_spec")
+            .short("s")
+            .long("abi_spec")
+            .help("Path of the ABI specification, used with --abi")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(ABI_SPEC_DEFAULT_PATH)
         ).arg(Arg::with_name("abi")
             .long("abi")
             .help("Use the ABI")
@@ -230,6 +377,50 @@ fn cli() -> Result<(), String> {
             .help("Read arguments from stdin")
             .conflicts_with("arguments")
             .required(false)
+        ).arg(Arg::with_name("inputs")
+            .long("inputs")
+            .help("Path of a file to read arguments from: a JSON file of ABI inputs, or a file of space-separated field elements")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with_all(&["arguments", "stdin"])
+            .required(false)
+        ).arg(Arg::with_name("batch")
+            .long("batch")
+            .help("Path of a JSON lines file of ABI inputs, to compute one witness per line. Witnesses are written to the output path suffixed with the line number")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with_all(&["arguments", "stdin", "inputs"])
+            .required(false)
+        ).arg(Arg::with_name("trace")
+            .long("trace")
+            .help("Path of a JSON file to record every statement evaluated to. Execution goes on after unsatisfied constraints, to record the following statements")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with("batch")
+            .required(false)
+        ).arg(Arg::with_name("stop-at-failure")
+            .long("stop-at-failure")
+            .help("Stop tracing at the first unsatisfied constraint")
+            .requires("trace")
+            .required(false)
+        ).arg(Arg::with_name("names")
+            .long("names")
+            .help("Write the name of each variable in the source code next to its value in the witness, and name the variables in the logs and the trace")
+            .required(false)
+        ).arg(Arg::with_name("wtns")
+            .long("wtns")
+            .help("Path of a file to also write the witness to in the iden3 '.wtns' format, in the order of the wires of `export-r1cs`")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with("batch")
+            .required(false)
+        ).arg(Arg::with_name("wtns-json")
+            .long("wtns-json")
+            .help("Path of a file to also write the witness to as a JSON array of values, in the order of the wires of `export-r1cs`")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with("batch")
+            .required(false)
         ).arg(Arg::with_name("light")
             .long("light")
             .help("Skip logs and human readable output")
@@ -278,10 +469,65 @@ fn cli() -> Result<(), String> {
             .takes_value(true)
             .required(false)
             .default_value(&default_scheme)
+        ).arg(Arg::with_name("batch")
+            .long("batch")
+            .help("Paths of witness files to generate proofs for, reading the proving key once. The proof for the n-th witness is written to the proof path suffixed with n. Only available for G16")
+            .value_name("FILE")
+            .takes_value(true)
+            .multiple(true)
+            .required(false)
+        ).arg(Arg::with_name("threads")
+            .long("threads")
+            .help("Number of threads to generate proofs with in batch mode. Each proof already uses all cores, so this mostly helps with many proofs for a small program")
+            .value_name("N")
+            .takes_value(true)
+            .required(false)
+            .default_value("1")
+        )
+    )
+    .subcommand(SubCommand::with_name("verify")
+        .about("Verifies a proof against a verification key.")
+        .arg(Arg::with_name("verification-key-path")
+            .short("v")
+            .long("verification-key-path")
+            .help("Path of the verification key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("proofpath")
+            .short("j")
+            .long("proofpath")
+            .help("Path of the JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        ).arg(Arg::with_name("inputs")
+            .long("inputs")
+            .help("Path of a JSON file containing the expected public inputs as an array of decimal strings. Defaults to the inputs embedded in the proof")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        ).arg(Arg::with_name("batch")
+            .long("batch")
+            .help("Path of a directory of JSON proof files, or of a file with one JSON proof per line, to verify together against the same verification key. Only available for G16")
+            .value_name("PATH")
+            .takes_value(true)
+            .required(false)
+            .conflicts_with("inputs")
+        ).arg(Arg::with_name("proving-scheme")
+            .short("s")
+            .long("proving-scheme")
+            .help("Proving scheme to use to verify the proof. Available options are G16 (default), PGHR13 and GM17")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(&default_scheme)
         )
     )
      .subcommand(SubCommand::with_name("print-proof")
-        .about("Prints proof in chosen format [remix, json]")
+        .about("Prints proof in chosen format [remix, json, avm]")
         .arg(Arg::with_name("proofpath")
             .short("j")
             .long("proofpath")
@@ -294,10 +540,206 @@ fn cli() -> Result<(), String> {
             .short("f")
             .long("format")
             .value_name("FORMAT")
-            .help("Format in which the proof should be printed. [remix, json]")
+            .help("Format in which the proof should be printed. [remix, json, avm]")
+            .takes_value(true)
+            .possible_values(&["remix", "json", "avm"])
+            .required(true)
+        ).arg(Arg::with_name("proving-scheme")
+            .short("s")
+            .long("proving-scheme")
+            .help("Proving scheme the proof was generated with, which sets its layout in the avm format. Available options are G16 (default), PGHR13 and GM17")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(&default_scheme)
+        ).arg(Arg::with_name("call-data")
+            .long("call-data")
+            .help("In the avm format, also prints the Aion ABI encoded call to the `verify` method of the AVM verifier")
+            .required(false)
+        ).arg(Arg::with_name("circuit-id")
+            .long("circuit-id")
+            .help("Index of the circuit to verify the proof for, when calling a registry contract exported from several verification keys")
+            .value_name("ID")
+            .takes_value(true)
+            .required(false)
+            .requires("call-data")
+        )
+    )
+    .subcommand(SubCommand::with_name("mpc")
+        .about("Runs a multi-party Groth16 setup ceremony for a given constraint system")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("init")
+            .about("Initializes the ceremony parameters")
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of compiled code")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(FLATTENED_CODE_DEFAULT_PATH)
+            ).arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the initial parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(MPC_PARAMETERS_DEFAULT_PATH)
+            ).arg(Arg::with_name("phase1")
+                .long("phase1")
+                .help("Path of a Powers of Tau file to take the circuit-independent parameters from")
+                .value_name("FILE")
+                .takes_value(true)
+                .required_unless("insecure-local-phase1")
+            ).arg(Arg::with_name("insecure-local-phase1")
+                .long("insecure-local-phase1")
+                .help("Sample the circuit-independent parameters locally instead of taking them from a Powers of Tau file. Whoever runs this can forge proofs: for testing only")
+                .required(false)
+                .conflicts_with("phase1")
+            )
+        )
+        .subcommand(SubCommand::with_name("contribute")
+            .about("Adds a random contribution to the ceremony parameters")
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the current parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(MPC_PARAMETERS_DEFAULT_PATH)
+            ).arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the new parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(true)
+            ).arg(Arg::with_name("entropy")
+                .short("e")
+                .long("entropy")
+                .help("Additional randomness, mixed with randomness from the operating system")
+                .value_name("STRING")
+                .takes_value(true)
+                .required(false)
+                .default_value("")
+            )
+        )
+        .subcommand(SubCommand::with_name("beacon")
+            .about("Adds a contribution derived from a public random beacon to the ceremony parameters")
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the current parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(MPC_PARAMETERS_DEFAULT_PATH)
+            ).arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the new parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(true)
+            ).arg(Arg::with_name("hash")
+                .long("hash")
+                .help("Beacon value as a hex string, for example a block hash")
+                .value_name("HEX")
+                .takes_value(true)
+                .required(true)
+            ).arg(Arg::with_name("exponent")
+                .short("n")
+                .long("exponent")
+                .help("Hash the beacon value 2^n times before using it")
+                .value_name("N")
+                .takes_value(true)
+                .required(false)
+                .default_value("10")
+            )
+        )
+        .subcommand(SubCommand::with_name("verify")
+            .about("Verifies all contributions to the ceremony parameters and prints their hashes")
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the parameters file to verify")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(MPC_PARAMETERS_DEFAULT_PATH)
+            ).arg(Arg::with_name("initial")
+                .long("initial")
+                .help("Path of the parameters file produced by `mpc init`")
+                .value_name("FILE")
+                .takes_value(true)
+                .required_unless("phase1")
+                .conflicts_with("phase1")
+            ).arg(Arg::with_name("phase1")
+                .long("phase1")
+                .help("Path of the Powers of Tau file used by `mpc init`, to recompute the initial parameters from")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+            ).arg(Arg::with_name("circuit")
+                .short("c")
+                .long("circuit")
+                .help("Path of compiled code")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(FLATTENED_CODE_DEFAULT_PATH)
+            )
+        )
+        .subcommand(SubCommand::with_name("export")
+            .about("Exports the proving and verification keys defined by the ceremony parameters")
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the final parameters file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(MPC_PARAMETERS_DEFAULT_PATH)
+            ).arg(Arg::with_name("proving-key-path")
+                .short("p")
+                .long("proving-key-path")
+                .help("Path of the generated proving key file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(PROVING_KEY_DEFAULT_PATH)
+            ).arg(Arg::with_name("verification-key-path")
+                .short("v")
+                .long("verification-key-path")
+                .help("Path of the generated verification key file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+            )
+        )
+    )
+    .subcommand(SubCommand::with_name("serve")
+        .about("Serves JSON-RPC requests over HTTP to compute witnesses, generate proofs and verify them, keeping programs and keys in memory. Only available for G16")
+        .arg(Arg::with_name("program")
+            .short("p")
+            .long("program")
+            .help("Name and directory of a program to serve, as NAME=DIR. The directory contains the compiled program and, optionally, the proving and verification keys, under their default names")
+            .value_name("NAME=DIR")
             .takes_value(true)
-            .possible_values(&["remix", "json"])
+            .multiple(true)
+            .number_of_values(1)
             .required(true)
+        ).arg(Arg::with_name("address")
+            .short("a")
+            .long("address")
+            .help("Address to listen on")
+            .value_name("ADDRESS")
+            .takes_value(true)
+            .required(false)
+            .default_value(SERVE_DEFAULT_ADDRESS)
         )
     )
     .get_matches();
@@ -326,10 +768,14 @@ fn cli() -> Result<(), String> {
 
             let mut reader = BufReader::new(file);
 
-            let program_flattened: ir::Prog<FieldPrime> =
+            let abi_spec_path = Path::new(sub_matches.value_of("abi_spec").unwrap());
+
+            let artifacts: CompilationArtifacts<FieldPrime> =
                 compile(&mut reader, Some(location), Some(resolve))
                     .map_err(|e| format!("Compilation failed:\n\n {}", e))?;
 
+            let program_flattened = artifacts.prog();
+
             // number of constraints the flattened program will translate to.
             let num_constraints = program_flattened.constraint_count();
 
@@ -337,11 +783,26 @@ fn cli() -> Result<(), String> {
             let bin_output_file = File::create(&bin_output_path)
                 .map_err(|why| format!("couldn't create {}: {}", bin_output_path.display(), why))?;
 
-            let mut writer = BufWriter::new(bin_output_file);
-
-            serialize_into(&mut writer, &program_flattened, Infinite)
+            program_flattened
+                .write(BufWriter::new(bin_output_file))
                 .map_err(|_| "Unable to write data to file.".to_string())?;
 
+            // write the ABI of the program
+            let abi_spec_file = File::create(&abi_spec_path)
+                .map_err(|why| format!("couldn't create {}: {}", abi_spec_path.display(), why))?;
+
+            serde_json::to_writer_pretty(BufWriter::new(abi_spec_file), artifacts.abi())
+                .map_err(|why| format!("couldn't write {}: {}", abi_spec_path.display(), why))?;
+
+            // print the variables with their names and the statements with their locations in the
+            // source code if requested
+            let hr_program = program_flattened
+                .annotated(
+                    sub_matches.is_present("names"),
+                    sub_matches.is_present("locations"),
+                )
+                .to_string();
+
             if !light {
                 // write human-readable output file
                 let hr_output_file = File::create(&hr_output_path).map_err(|why| {
@@ -349,7 +810,7 @@ fn cli() -> Result<(), String> {
                 })?;
 
                 let mut hrofb = BufWriter::new(hr_output_file);
-                write!(&mut hrofb, "{}\n", program_flattened)
+                write!(&mut hrofb, "{}\n", hr_program)
                     .map_err(|_| "Unable to write data to file.".to_string())?;
                 hrofb
                     .flush()
@@ -358,10 +819,11 @@ fn cli() -> Result<(), String> {
 
             if !light {
                 // debugging output
-                println!("Compiled program:\n{}", program_flattened);
+                println!("Compiled program:\n{}", hr_program);
             }
 
             println!("Compiled code written to '{}'", bin_output_path.display());
+            println!("ABI specification written to '{}'", abi_spec_path.display());
 
             if !light {
                 println!("Human readable code to '{}'", hr_output_path.display());
@@ -369,41 +831,181 @@ fn cli() -> Result<(), String> {
 
             println!("Number of constraints: {}", num_constraints);
         }
-        ("compute-witness", Some(sub_matches)) => {
-            println!("Computing witness...");
+        ("profile", Some(sub_matches)) => {
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+            let ir_prog = read_program(path)?;
 
-            // read compiled program
+            let profile = ir_prog.profile();
+
+            print!("{}", profile);
+
+            if let Some(folded_path) = sub_matches.value_of("folded") {
+                let folded_path = Path::new(folded_path);
+                fs::write(folded_path, profile.folded())
+                    .map_err(|why| format!("couldn't write {}: {}", folded_path.display(), why))?;
+                println!("\nCall stacks written to '{}'", folded_path.display());
+            }
+        }
+        ("export-r1cs", Some(sub_matches)) => {
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+            let ir_prog = read_program(path)?;
+
+            let json = sub_matches.is_present("json");
+            let output_path = Path::new(sub_matches.value_of("output").unwrap_or(match json {
+                true => R1CS_JSON_DEFAULT_PATH,
+                false => R1CS_DEFAULT_PATH,
+            }));
+
+            let r1cs = ir_prog.r1cs();
+
+            let output_file = File::create(&output_path)
+                .map_err(|why| format!("couldn't create {}: {}", output_path.display(), why))?;
+            let writer = BufWriter::new(output_file);
+
+            match json {
+                true => r1cs.write_json(writer),
+                false => r1cs.write(writer),
+            }
+            .map_err(|why| format!("couldn't write {}: {}", output_path.display(), why))?;
+
+            println!(
+                "Constraint system with {} wires and {} constraints written to '{}'",
+                r1cs.wires.len(),
+                r1cs.constraints.len(),
+                output_path.display()
+            );
+        }
+        ("import-r1cs", Some(sub_matches)) => {
             let path = Path::new(sub_matches.value_of("input").unwrap());
             let file = File::open(&path)
                 .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
 
-            let mut reader = BufReader::new(file);
+            let r1cs: ir::R1cs<FieldPrime> = ir::R1cs::read(BufReader::new(file))
+                .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+            // keep the variable of each wire to convert the witness
+            let wires = r1cs.wires.clone();
+            let ir_prog: ir::Prog<FieldPrime> = r1cs.into();
+
+            if let Some(wtns_path) = sub_matches.value_of("wtns") {
+                let wtns_path = Path::new(wtns_path);
+                let file = File::open(&wtns_path)
+                    .map_err(|why| format!("couldn't open {}: {}", wtns_path.display(), why))?;
+
+                let witness = ir::Witness::read_wtns(&wires, BufReader::new(file))
+                    .map_err(|why| format!("couldn't read {}: {}", wtns_path.display(), why))?;
+
+                ir_prog
+                    .check_witness(&witness)
+                    .map_err(|e| format!("Witness does not satisfy the constraints: {}", e))?;
+
+                let witness_path = Path::new(sub_matches.value_of("witness").unwrap());
+                let witness_file = File::create(&witness_path).map_err(|why| {
+                    format!("couldn't create {}: {}", witness_path.display(), why)
+                })?;
+                witness
+                    .write(BufWriter::new(witness_file))
+                    .map_err(|why| format!("could not save witness: {:?}", why))?;
+
+                println!("Witness written to '{}'", witness_path.display());
+            }
+
+            let output_path = Path::new(sub_matches.value_of("output").unwrap());
+            let output_file = File::create(&output_path)
+                .map_err(|why| format!("couldn't create {}: {}", output_path.display(), why))?;
+
+            ir_prog
+                .write(BufWriter::new(output_file))
+                .map_err(|_| "Unable to write data to file.".to_string())?;
+
+            println!("Compiled code written to '{}'", output_path.display());
+            println!("Number of constraints: {}", ir_prog.constraint_count());
+        }
+        ("compute-witness", Some(sub_matches)) => {
+            println!("Computing witness...");
+
+            // read compiled program
+            let ir_prog = read_program(Path::new(sub_matches.value_of("input").unwrap()))?;
 
-            let ir_prog: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| why.to_string())?;
+            let names = match sub_matches.is_present("names") {
+                true => ir_prog.names(),
+                false => BTreeMap::new(),
+            };
 
             // print deserialized flattened program
             if !sub_matches.is_present("light") {
-                println!("{}", ir_prog);
+                match sub_matches.is_present("names") {
+                    true => println!("{}", ir_prog.named()),
+                    false => println!("{}", ir_prog),
+                }
             }
 
-            let signature = ir_prog.signature.clone();
-
             let is_stdin = sub_matches.is_present("stdin");
-            let is_abi = sub_matches.is_present("abi");
+            let inputs_path = sub_matches.value_of("inputs").map(Path::new);
+            let batch_path = sub_matches.value_of("batch").map(Path::new);
+            // JSON input files are always read with the ABI
+            let is_abi = sub_matches.is_present("abi")
+                || batch_path.is_some()
+                || inputs_path
+                    .map(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+                    .unwrap_or(false);
+
+            // with the ABI, read the interface of the program from the specification written by `compile`
+            let signature = match is_abi {
+                true => {
+                    let path = Path::new(sub_matches.value_of("abi_spec").unwrap());
+                    let file = File::open(&path)
+                        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+                    let abi: Abi = serde_json::from_reader(BufReader::new(file))
+                        .map_err(|why| format!("couldn't parse {}: {}", path.display(), why))?;
+
+                    abi.signature()
+                }
+                false => ir_prog.signature.clone(),
+            };
 
-            if !is_stdin && is_abi {
+            let output_path = Path::new(sub_matches.value_of("output").unwrap());
+
+            if let Some(batch_path) = batch_path {
+                return compute_witness_batch(
+                    &ir_prog,
+                    &signature,
+                    batch_path,
+                    output_path,
+                    &names,
+                );
+            }
+
+            if is_abi && !is_stdin && inputs_path.is_none() {
                 return Err(
-                    "ABI input as inline argument is not supported. Please use `--stdin`.".into(),
+                    "ABI input as inline argument is not supported. Please use `--stdin` or `--inputs`."
+                        .into(),
                 );
             }
 
+            // read the arguments from a file or from stdin, unless they are passed inline
+            let input = match inputs_path {
+                Some(path) => Some(
+                    fs::read_to_string(&path)
+                        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?,
+                ),
+                None if is_stdin && (is_abi || ir_prog.arguments_count() > 0) => {
+                    let mut input = String::new();
+                    stdin()
+                        .read_to_string(&mut input)
+                        .map_err(|why| format!("couldn't read stdin: {}", why))?;
+                    Some(input)
+                }
+                None => None,
+            };
+
             use zokrates_abi::Inputs;
 
             // get arguments
-            let arguments = match is_stdin {
+            let arguments = match input {
                 // take inline arguments
-                false => {
+                None => {
                     let arguments = sub_matches.values_of("arguments");
                     arguments
                         .map(|a| {
@@ -413,49 +1015,48 @@ fn cli() -> Result<(), String> {
                         .unwrap_or(Ok(vec![]))
                         .map(|v| Inputs::Raw(v))
                 }
-                // take stdin arguments
-                true => {
-                    let mut stdin = stdin();
-                    let mut input = String::new();
-
-                    match is_abi {
-                        true => match stdin.read_to_string(&mut input) {
-                            Ok(_) => {
-                                use zokrates_abi::parse_strict;
-
-                                parse_strict(&input, signature.inputs)
-                                    .map(|parsed| Inputs::Abi(parsed))
-                                    .map_err(|why| why.to_string())
-                            }
-                            Err(_) => Err(String::from("???")),
-                        },
-                        false => match ir_prog.arguments_count() {
-                            0 => Ok(Inputs::Raw(vec![])),
-                            _ => match stdin.read_to_string(&mut input) {
-                                Ok(_) => {
-                                    input.retain(|x| x != '\n');
-                                    input
-                                        .split(" ")
-                                        .map(|x| {
-                                            FieldPrime::try_from_dec_str(x)
-                                                .map_err(|_| x.to_string())
-                                        })
-                                        .collect::<Result<Vec<_>, _>>()
-                                        .map(|v| Inputs::Raw(v))
-                                }
-                                Err(_) => Err(String::from("???")),
-                            },
-                        },
+                // take arguments from stdin or from a file
+                Some(input) => match is_abi {
+                    true => {
+                        use zokrates_abi::parse_strict_signature;
+
+                        parse_strict_signature(&input, &signature)
+                            .map(|parsed| Inputs::Abi(parsed))
+                            .map_err(|why| why.to_string())
                     }
-                }
+                    false => input
+                        .split_whitespace()
+                        .map(|x| FieldPrime::try_from_dec_str(x).map_err(|_| x.to_string()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|v| Inputs::Raw(v)),
+                },
             }
             .map_err(|e| format!("Could not parse argument: {}", e))?;
 
-            let witness = ir_prog
-                .execute(&arguments.encode())
-                .map_err(|e| format!("Execution failed: {}", e))?;
+            let result = match sub_matches.value_of("trace") {
+                Some(trace_path) => {
+                    let trace_path = Path::new(trace_path);
+                    let mut trace = ir::Trace::new(sub_matches.is_present("stop-at-failure"))
+                        .names(names.clone());
+
+                    let result = ir_prog.execute_with(&arguments.encode(), &mut trace);
+
+                    // the trace is written even if execution fails, to find out why
+                    let trace_file = File::create(&trace_path).map_err(|why| {
+                        format!("couldn't create {}: {}", trace_path.display(), why)
+                    })?;
+                    serde_json::to_writer_pretty(BufWriter::new(trace_file), &trace).map_err(
+                        |why| format!("couldn't write {}: {}", trace_path.display(), why),
+                    )?;
 
-            use zokrates_abi::Decode;
+                    println!("Execution trace written to '{}'", trace_path.display());
+
+                    result
+                }
+                None => ir_prog.execute(&arguments.encode()),
+            };
+
+            let witness = result.map_err(|e| format!("Execution failed: {}", e))?;
 
             let results_json_value: serde_json::Value =
                 zokrates_abi::CheckedValues::decode(witness.return_values(), signature.outputs)
@@ -463,30 +1064,43 @@ fn cli() -> Result<(), String> {
 
             println!("\nWitness: \n\n{}", results_json_value.to_string());
 
-            // write witness to file
-            let output_path = Path::new(sub_matches.value_of("output").unwrap());
-            let output_file = File::create(&output_path)
-                .map_err(|why| format!("couldn't create {}: {}", output_path.display(), why))?;
+            let values_path = write_witness(&witness, &results_json_value, output_path, &names)?;
 
-            let writer = BufWriter::new(output_file);
+            println!("Return values written to '{}'", values_path.display());
+
+            // write the values of the wires of the constraint system for external provers
+            let wtns_path = sub_matches.value_of("wtns").map(Path::new);
+            let wtns_json_path = sub_matches.value_of("wtns-json").map(Path::new);
+
+            if wtns_path.is_some() || wtns_json_path.is_some() {
+                let wires = ir_prog.r1cs().wires;
+
+                if let Some(path) = wtns_path {
+                    let file = File::create(&path)
+                        .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?;
+                    witness
+                        .write_wtns(&wires, BufWriter::new(file))
+                        .map_err(|why| format!("couldn't write {}: {}", path.display(), why))?;
+                    println!("Witness written to '{}'", path.display());
+                }
 
-            witness
-                .write(writer)
-                .map_err(|why| format!("could not save witness: {:?}", why))?;
+                if let Some(path) = wtns_json_path {
+                    let file = File::create(&path)
+                        .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?;
+                    witness
+                        .write_wires_json(&wires, BufWriter::new(file))
+                        .map_err(|why| format!("couldn't write {}: {}", path.display(), why))?;
+                    println!("Witness written to '{}'", path.display());
+                }
+            }
         }
         ("setup", Some(sub_matches)) => {
-            let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+            let scheme_str = sub_matches.value_of("proving-scheme").unwrap();
+            let scheme = get_scheme(scheme_str)?;
 
             println!("Performing setup...");
 
-            let path = Path::new(sub_matches.value_of("input").unwrap());
-            let file = File::open(&path)
-                .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
-
-            let mut reader = BufReader::new(file);
-
-            let program: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| format!("{:?}", why))?;
+            let program = read_program(Path::new(sub_matches.value_of("input").unwrap()))?;
 
             // print deserialized flattened program
             if !sub_matches.is_present("light") {
@@ -494,11 +1108,30 @@ fn cli() -> Result<(), String> {
             }
 
             // get paths for proving and verification keys
-            let pk_path = sub_matches.value_of("proving-key-path").unwrap();
-            let vk_path = sub_matches.value_of("verification-key-path").unwrap();
+            let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
 
             // run setup phase
-            scheme.setup(program, pk_path, vk_path);
+            let keypair = match sub_matches.value_of("phase1") {
+                Some(phase1_path) => {
+                    if scheme_str.to_lowercase() != "g16" {
+                        return Err(String::from(
+                            "Phase 1 files can only be used with the G16 proving scheme",
+                        ));
+                    }
+
+                    let phase1 = read_phase1(Path::new(phase1_path), &program)?;
+
+                    G16 {}.setup_with_phase1(program, &phase1)
+                }
+                None => scheme.setup(program),
+            }
+            .map_err(|e| format!("Setup failed: {}", e))?;
+
+            fs::write(pk_path, &keypair.pk)
+                .map_err(|why| format!("couldn't write {}: {}", pk_path.display(), why))?;
+            fs::write(vk_path, &keypair.vk)
+                .map_err(|why| format!("couldn't write {}: {}", vk_path.display(), why))?;
         }
         ("export-verifier", Some(sub_matches)) => {
             {
@@ -508,11 +1141,12 @@ fn cli() -> Result<(), String> {
 
                 // read vk file
                 let input_path = Path::new(sub_matches.value_of("input").unwrap());
-                let input_file = File::open(&input_path)
+                let vk = fs::read_to_string(&input_path)
                     .map_err(|why| format!("couldn't open {}: {}", input_path.display(), why))?;
-                let reader = BufReader::new(input_file);
 
-                let verifier = scheme.export_solidity_verifier(reader, is_abiv2);
+                let verifier = scheme
+                    .export_solidity_verifier(&vk, is_abiv2)
+                    .map_err(|e| format!("Could not export verifier: {}", e))?;
 
                 //write output file
                 let output_path = Path::new(sub_matches.value_of("output").unwrap());
@@ -530,17 +1164,89 @@ fn cli() -> Result<(), String> {
         }
         ("export-avm-verifier", Some(sub_matches)) => {
             {
-                let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+                let input_paths: Vec<_> = sub_matches.values_of("input").unwrap().collect();
+                let schemes = sub_matches
+                    .values_of("proving-scheme")
+                    .unwrap()
+                    .map(get_scheme)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if schemes.len() != 1 && schemes.len() != input_paths.len() {
+                    return Err(format!(
+                        "Expected one proving scheme, or one for each of the {} verification keys, found {}",
+                        input_paths.len(),
+                        schemes.len()
+                    ));
+                }
+                let proof_paths: Option<Vec<_>> =
+                    sub_matches.values_of("proof").map(|p| p.collect());
+                if let Some(ref proof_paths) = proof_paths {
+                    if proof_paths.len() != input_paths.len() {
+                        return Err(format!(
+                            "Expected one proof for each of the {} verification keys, found {}",
+                            input_paths.len(),
+                            proof_paths.len()
+                        ));
+                    }
+                }
+                let names = JavaNames::new(
+                    sub_matches.value_of("package").unwrap(),
+                    sub_matches.value_of("class").unwrap(),
+                    sub_matches.value_of("prefix").unwrap_or(""),
+                )?;
                 println!("Exporting AVM verifier...");
 
-                // read vk file
-                let input_path = Path::new(sub_matches.value_of("input").unwrap());
-                let input_file = File::open(&input_path)
-                    .map_err(|why| format!("couldn't open {}: {}", input_path.display(), why))?;
-                let reader = BufReader::new(input_file);
+                // store the java contracts in memory
+                let mut files = HashMap::new();
+                let mut call_data = vec![];
+
+                for (id, input_path) in input_paths.iter().enumerate() {
+                    let scheme = schemes[if schemes.len() == 1 { 0 } else { id }];
+
+                    // read vk file
+                    let input_path = Path::new(input_path);
+                    let vk = fs::read_to_string(&input_path).map_err(|why| {
+                        format!("couldn't open {}: {}", input_path.display(), why)
+                    })?;
+
+                    let (names, is_callable) = match input_paths.len() {
+                        1 => (names.clone(), true),
+                        _ => {
+                            println!("Circuit {}: {}", id, input_path.display());
+                            (names.circuit(id), false)
+                        }
+                    };
+
+                    let verifier = scheme
+                        .export_avm_verifier(&vk, &names, is_callable)
+                        .map_err(|e| format!("Could not export verifier: {}", e))?;
+
+                    files.insert(names.file_name(), verifier);
+
+                    // encode the call verifying the sample proof
+                    if let Some(ref proof_paths) = proof_paths {
+                        let proof_path = Path::new(proof_paths[id]);
+                        let proof = fs::read_to_string(&proof_path).map_err(|why| {
+                            format!("couldn't open {}: {}", proof_path.display(), why)
+                        })?;
+                        let proof = scheme
+                            .export_avm_proof(&proof)
+                            .map_err(|e| format!("Could not encode proof: {}", e))?;
+
+                        call_data.push(proof.call_data(match is_callable {
+                            true => None,
+                            false => Some(id as i32),
+                        }));
+                    }
+                }
 
-                // store the verifier java contract in memory
-                let verifier = scheme.export_avm_verifier(reader);
+                if input_paths.len() > 1 {
+                    files.insert(names.file_name(), java_registry(&names, input_paths.len()));
+                }
+                files.extend(java_lib(&names));
+                if proof_paths.is_some() {
+                    let (name, test) = java_test(&names, input_paths.len(), &call_data);
+                    files.insert(name, test);
+                }
 
                 // write a directory
                 let output_path = Path::new(sub_matches.value_of("output").unwrap());
@@ -550,17 +1256,6 @@ fn cli() -> Result<(), String> {
 
                 assert_eq!(output_path.is_dir(), true, "output path must be a directory");
 
-                let mut files = HashMap::new();
-                files.insert("Verifier.java", verifier);
-                files.insert("Fp.java", JAVA_LIB_FP.parse().unwrap());
-                files.insert("Fp2.java", JAVA_LIB_FP2.parse().unwrap());
-                files.insert("G1.java", JAVA_LIB_G1.parse().unwrap());
-                files.insert("G1Point.java", JAVA_LIB_G1POINT.parse().unwrap());
-                files.insert("G2.java", JAVA_LIB_G2.parse().unwrap());
-                files.insert("G2Point.java", JAVA_LIB_G2POINT.parse().unwrap());
-                files.insert("Pairing.java", JAVA_LIB_PAIRING.parse().unwrap());
-                files.insert("Util.java", JAVA_LIB_UTIL.parse().unwrap());
-
                 // write out all the files to disk
                 for (name, content) in &files {
                     println!("writing out file: {}", name);
@@ -582,34 +1277,189 @@ fn cli() -> Result<(), String> {
         ("generate-proof", Some(sub_matches)) => {
             println!("Generating proof...");
 
-            let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+            let scheme_str = sub_matches.value_of("proving-scheme").unwrap();
+            let scheme = get_scheme(scheme_str)?;
 
-            // deserialize witness
-            let witness_path = Path::new(sub_matches.value_of("witness").unwrap());
-            let witness_file = match File::open(&witness_path) {
-                Ok(file) => file,
-                Err(why) => panic!("couldn't open {}: {}", witness_path.display(), why),
-            };
+            let pk_path = Path::new(sub_matches.value_of("provingkey").unwrap());
+            let pk = fs::read(&pk_path)
+                .map_err(|why| format!("couldn't open {}: {}", pk_path.display(), why))?;
+
+            let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+
+            let program = read_program(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+            match sub_matches.values_of("batch") {
+                Some(witness_paths) => {
+                    if scheme_str.to_lowercase() != "g16" {
+                        return Err(String::from(
+                            "Batch proof generation is only available for the G16 proving scheme",
+                        ));
+                    }
+
+                    let threads = sub_matches
+                        .value_of("threads")
+                        .unwrap()
+                        .parse::<usize>()
+                        .map_err(|why| format!("Invalid number of threads: {}", why))?;
+
+                    let witness_paths: Vec<_> = witness_paths.map(Path::new).collect();
+
+                    let witnesses = witness_paths
+                        .iter()
+                        .map(|path| {
+                            let file = File::open(&path).map_err(|why| {
+                                format!("couldn't open {}: {}", path.display(), why)
+                            })?;
+                            ir::Witness::read(file)
+                                .map_err(|why| format!("could not load witness: {:?}", why))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    let proofs = G16 {}
+                        .generate_proofs(program, witnesses, &pk, threads)
+                        .map_err(|e| format!("Proof generation failed: {}", e))?;
+
+                    let mut failed = vec![];
+
+                    for (i, (witness_path, proof)) in witness_paths.iter().zip(proofs).enumerate() {
+                        match proof {
+                            Ok(proof) => {
+                                let path = batch_output_path(proof_path, i + 1);
+                                fs::write(&path, proof).map_err(|why| {
+                                    format!("couldn't write {}: {}", path.display(), why)
+                                })?;
+                                println!(
+                                    "Proof for {} written to '{}'",
+                                    witness_path.display(),
+                                    path.display()
+                                );
+                            }
+                            Err(e) => failed.push(format!(
+                                "Proof generation failed for {}: {}",
+                                witness_path.display(),
+                                e
+                            )),
+                        }
+                    }
 
-            let witness = ir::Witness::read(witness_file)
-                .map_err(|why| format!("could not load witness: {:?}", why))?;
+                    if !failed.is_empty() {
+                        return Err(failed.join("\n"));
+                    }
+                }
+                None => {
+                    // deserialize witness
+                    let witness_path = Path::new(sub_matches.value_of("witness").unwrap());
+                    let witness_file = match File::open(&witness_path) {
+                        Ok(file) => file,
+                        Err(why) => panic!("couldn't open {}: {}", witness_path.display(), why),
+                    };
+
+                    let witness = ir::Witness::read(witness_file)
+                        .map_err(|why| format!("could not load witness: {:?}", why))?;
+
+                    let proof = scheme
+                        .generate_proof(program, witness, &pk)
+                        .map_err(|e| format!("Proof generation failed: {}", e))?;
+
+                    fs::write(&proof_path, proof).map_err(|why| {
+                        format!("couldn't write {}: {}", proof_path.display(), why)
+                    })?;
+                }
+            }
 
-            let pk_path = sub_matches.value_of("provingkey").unwrap();
-            let proof_path = sub_matches.value_of("proofpath").unwrap();
+            println!("generate-proof successful");
+        }
+        ("verify", Some(sub_matches)) => {
+            let scheme_str = sub_matches.value_of("proving-scheme").unwrap();
+            let scheme = get_scheme(scheme_str)?;
+
+            println!("Verifying proof...");
+
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+            let vk = fs::read_to_string(&vk_path)
+                .map_err(|why| format!("couldn't open {}: {}", vk_path.display(), why))?;
+
+            match sub_matches.value_of("batch") {
+                Some(batch_path) => {
+                    if scheme_str.to_lowercase() != "g16" {
+                        return Err(String::from(
+                            "Batch verification is only available for the G16 proving scheme",
+                        ));
+                    }
 
-            let program_path = Path::new(sub_matches.value_of("input").unwrap());
-            let program_file = File::open(&program_path)
-                .map_err(|why| format!("couldn't open {}: {}", program_path.display(), why))?;
+                    let (names, proofs): (Vec<_>, Vec<_>) =
+                        read_proof_batch(Path::new(batch_path))?.into_iter().unzip();
 
-            let mut reader = BufReader::new(program_file);
+                    let results = G16 {}
+                        .verify_batch(&vk, &proofs)
+                        .map_err(|e| format!("Could not verify proofs: {}", e))?;
 
-            let program: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| format!("{:?}", why))?;
+                    let mut failed = 0;
+                    for (name, result) in names.iter().zip(results) {
+                        match result {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                failed += 1;
+                                println!("Proof {} failed verification", name);
+                            }
+                            Err(e) => {
+                                failed += 1;
+                                println!("Proof {} could not be read: {}", name, e);
+                            }
+                        }
+                    }
 
-            println!(
-                "generate-proof successful: {:?}",
-                scheme.generate_proof(program, witness, pk_path, proof_path)
-            );
+                    if failed > 0 {
+                        return Err(format!(
+                            "{} of {} proofs failed verification",
+                            failed,
+                            names.len()
+                        ));
+                    }
+
+                    println!("All {} proofs verified successfully.", names.len());
+                }
+                None => {
+                    let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+                    let proof = fs::read_to_string(&proof_path).map_err(|why| {
+                        format!("couldn't open {}: {}", proof_path.display(), why)
+                    })?;
+
+                    let inputs = match sub_matches.value_of("inputs") {
+                        Some(inputs_path) => {
+                            let inputs_path = Path::new(inputs_path);
+                            let inputs_file = File::open(&inputs_path).map_err(|why| {
+                                format!("couldn't open {}: {}", inputs_path.display(), why)
+                            })?;
+
+                            let values: Vec<String> = serde_json::from_reader(inputs_file)
+                                .map_err(|why| {
+                                    format!("couldn't parse {}: {}", inputs_path.display(), why)
+                                })?;
+
+                            Some(
+                                values
+                                    .iter()
+                                    .map(|v| {
+                                        FieldPrime::try_from_dec_str(v).map_err(|_| {
+                                            format!("Could not parse public input: {}", v)
+                                        })
+                                    })
+                                    .collect::<Result<Vec<_>, _>>()?,
+                            )
+                        }
+                        None => None,
+                    };
+
+                    match scheme
+                        .verify(&vk, &proof, inputs)
+                        .map_err(|e| format!("Could not verify proof: {}", e))?
+                    {
+                        true => println!("Proof verified successfully."),
+                        false => return Err("Proof verification failed".to_string()),
+                    }
+                }
+            }
         }
         ("print-proof", Some(sub_matches)) => {
             let format = sub_matches.value_of("format").unwrap();
@@ -645,9 +1495,172 @@ fn cli() -> Result<(), String> {
                     println!();
                     println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
                 }
+                "avm" => {
+                    let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+                    let circuit_id = sub_matches
+                        .value_of("circuit-id")
+                        .map(|id| {
+                            id.parse::<i32>()
+                                .map_err(|why| format!("Invalid circuit id {}: {}", id, why))
+                        })
+                        .transpose()?;
+
+                    let proof = scheme
+                        .export_avm_proof(&proof_object.to_string())
+                        .map_err(|e| format!("Could not encode proof: {}", e))?;
+                    let inputs: Vec<_> = proof.inputs.iter().map(|i| i.to_string()).collect();
+
+                    println!("~~~~~~~~ Copy the output below for the AVM verifier ~~~~~~~~");
+                    println!();
+                    println!("proof: 0x{}", to_hex(&proof.proof));
+                    println!("inputs: {}", serde_json::to_string(&inputs).unwrap());
+                    if sub_matches.is_present("call-data") {
+                        println!("call data: 0x{}", to_hex(&proof.call_data(circuit_id)));
+                    }
+                    println!();
+                    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+                }
                 _ => unreachable!(),
             }
         }
+        ("mpc", Some(sub_matches)) => match sub_matches.subcommand() {
+            ("init", Some(sub_matches)) => {
+                println!("Initializing MPC parameters...");
+
+                let program = read_program(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+                let params = match sub_matches.value_of("phase1") {
+                    Some(phase1_path) => {
+                        let phase1 = read_phase1(Path::new(phase1_path), &program)?;
+
+                        MPCParameters::from_phase1(program, &phase1)
+                    }
+                    None => MPCParameters::new(program),
+                }
+                .map_err(|e| format!("Initialization failed: {}", e))?;
+
+                write_mpc_parameters(&params, Path::new(sub_matches.value_of("output").unwrap()))?;
+            }
+            ("contribute", Some(sub_matches)) => {
+                let mut params =
+                    read_mpc_parameters(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+                println!("Contributing to MPC parameters...");
+
+                let hash = params
+                    .contribute(sub_matches.value_of("entropy").unwrap().as_bytes())
+                    .map_err(|e| format!("Contribution failed: {}", e))?;
+
+                write_mpc_parameters(&params, Path::new(sub_matches.value_of("output").unwrap()))?;
+
+                println!("Contribution hash: {}", to_hex(&hash));
+            }
+            ("beacon", Some(sub_matches)) => {
+                let beacon = from_hex(sub_matches.value_of("hash").unwrap())?;
+                let exponent = sub_matches
+                    .value_of("exponent")
+                    .unwrap()
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|n| *n < 64)
+                    .ok_or_else(|| {
+                        String::from("Beacon exponent must be a number smaller than 64")
+                    })?;
+
+                let mut params =
+                    read_mpc_parameters(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+                println!("Applying random beacon to MPC parameters...");
+
+                let hash = params.apply_beacon(&beacon, exponent);
+
+                write_mpc_parameters(&params, Path::new(sub_matches.value_of("output").unwrap()))?;
+
+                println!("Contribution hash: {}", to_hex(&hash));
+            }
+            ("verify", Some(sub_matches)) => {
+                let program = read_program(Path::new(sub_matches.value_of("circuit").unwrap()))?;
+                let initial = match sub_matches.value_of("phase1") {
+                    Some(phase1_path) => MPCParameters::from_phase1(
+                        program.clone(),
+                        &read_phase1(Path::new(phase1_path), &program)?,
+                    )
+                    .map_err(|e| format!("Initialization failed: {}", e))?,
+                    None => {
+                        read_mpc_parameters(Path::new(sub_matches.value_of("initial").unwrap()))?
+                    }
+                };
+                let params =
+                    read_mpc_parameters(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+                println!("Verifying MPC parameters...");
+
+                let hashes = params
+                    .verify(&program, &initial)
+                    .map_err(|e| format!("Verification failed: {}", e))?;
+
+                for (i, hash) in hashes.iter().enumerate() {
+                    println!("Contribution {}: {}", i + 1, to_hex(hash));
+                }
+
+                println!("MPC parameters verified successfully.");
+            }
+            ("export", Some(sub_matches)) => {
+                let params =
+                    read_mpc_parameters(Path::new(sub_matches.value_of("input").unwrap()))?;
+
+                let keypair = params
+                    .keypair()
+                    .map_err(|e| format!("Export failed: {}", e))?;
+
+                let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
+                let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+
+                fs::write(pk_path, &keypair.pk)
+                    .map_err(|why| format!("couldn't write {}: {}", pk_path.display(), why))?;
+                fs::write(vk_path, &keypair.vk)
+                    .map_err(|why| format!("couldn't write {}: {}", vk_path.display(), why))?;
+            }
+            _ => unreachable!(),
+        },
+        ("serve", Some(sub_matches)) => {
+            let mut programs = HashMap::new();
+
+            for program in sub_matches.values_of("program").unwrap() {
+                let mut parts = program.splitn(2, '=');
+                let (name, dir) = match (parts.next(), parts.next()) {
+                    (Some(name), Some(dir)) => (name, Path::new(dir)),
+                    _ => return Err(format!("Invalid program `{}`, expected NAME=DIR", program)),
+                };
+
+                let ir_prog = read_program(&dir.join(FLATTENED_CODE_DEFAULT_PATH))?;
+
+                // keys are optional, so that programs can be served to compute witnesses only
+                let pk_path = dir.join(PROVING_KEY_DEFAULT_PATH);
+                let proving_key = match pk_path.exists() {
+                    true => Some(read_proving_key(&pk_path)?),
+                    false => None,
+                };
+
+                let vk_path = dir.join(VERIFICATION_KEY_DEFAULT_PATH);
+                let verification_key = match fs::read_to_string(&vk_path) {
+                    Ok(vk) => Some(vk),
+                    Err(ref why) if why.kind() == io::ErrorKind::NotFound => None,
+                    Err(why) => {
+                        return Err(format!("couldn't open {}: {}", vk_path.display(), why))
+                    }
+                };
+
+                println!("Loaded program `{}` from {}", name, dir.display());
+
+                programs.insert(
+                    name.to_string(),
+                    serve::Program::new(ir_prog, proving_key, verification_key),
+                );
+            }
+
+            serve::Server::new(programs).listen(sub_matches.value_of("address").unwrap())?;
+        }
         _ => unreachable!(),
     }
     Ok(())
@@ -664,6 +1677,212 @@ fn get_scheme(scheme_str: &str) -> Result<&'static dyn ProofSystem, String> {
     }
 }
 
+fn read_program(path: &Path) -> Result<ir::Prog<FieldPrime>, String> {
+    let file =
+        File::open(&path).map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    ir::Prog::read(BufReader::new(file))
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))
+}
+
+fn read_mpc_parameters(path: &Path) -> Result<MPCParameters, String> {
+    let file =
+        File::open(&path).map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    MPCParameters::read(BufReader::new(file), true)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))
+}
+
+/// Reads the powers of tau needed to run the setup for `program` from the phase 1 file at `path`
+fn read_phase1(path: &Path, program: &ir::Prog<FieldPrime>) -> Result<Phase1, String> {
+    let domain_size =
+        Phase1::domain_size(program).map_err(|e| format!("couldn't synthesize program: {}", e))?;
+
+    let file =
+        File::open(&path).map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    Phase1::read(BufReader::new(file), domain_size)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))
+}
+
+fn read_proving_key(path: &Path) -> Result<G16ProvingKey, String> {
+    let pk = fs::read(&path).map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    G16 {}
+        .read_proving_key(&pk)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))
+}
+
+/// Reads the proofs to verify with `verify --batch`, each with a name to report failures with:
+/// the file name for a directory of proofs, or the line number for a JSON lines file
+fn read_proof_batch(path: &Path) -> Result<Vec<(String, String)>, String> {
+    if path.is_dir() {
+        let mut paths = fs::read_dir(&path)
+            .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+        paths.retain(|p| p.extension().map(|e| e == "json").unwrap_or(false));
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|p| {
+                fs::read_to_string(&p)
+                    .map(|proof| (p.display().to_string(), proof))
+                    .map_err(|why| format!("couldn't open {}: {}", p.display(), why))
+            })
+            .collect()
+    } else {
+        let proofs = fs::read_to_string(&path)
+            .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+        Ok(proofs
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (format!("{}:{}", path.display(), i + 1), line.to_string()))
+            .collect())
+    }
+}
+
+/// Writes `witness` to `path` with the `names` of its variables, and the return values of the
+/// program next to it at `returns_path(path)`. Returns the path of the return values
+fn write_witness(
+    witness: &ir::Witness<FieldPrime>,
+    return_values: &Value,
+    path: &Path,
+    names: &BTreeMap<FlatVariable, String>,
+) -> Result<PathBuf, String> {
+    let file = File::create(&path)
+        .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?;
+
+    witness
+        .write_with_names(names, BufWriter::new(file))
+        .map_err(|why| format!("could not save witness: {:?}", why))?;
+
+    let values_path = returns_path(path);
+    let values_file = File::create(&values_path)
+        .map_err(|why| format!("couldn't create {}: {}", values_path.display(), why))?;
+
+    serde_json::to_writer_pretty(BufWriter::new(values_file), return_values)
+        .map_err(|why| format!("couldn't write {}: {}", values_path.display(), why))?;
+
+    Ok(values_path)
+}
+
+/// Path of the return values written along the witness at `path`: `witness.returns.json` for
+/// `witness`. The suffix is appended rather than replacing the extension, so that it never
+/// overwrites the witness itself
+fn returns_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".returns.json");
+    path.with_file_name(name)
+}
+
+/// Computes a witness for each line of ABI inputs in the file at `inputs_path`, writing them to
+/// `output_path` suffixed with the line number, and reports the lines which failed
+fn compute_witness_batch(
+    program: &ir::Prog<FieldPrime>,
+    signature: &Signature,
+    inputs_path: &Path,
+    output_path: &Path,
+    names: &BTreeMap<FlatVariable, String>,
+) -> Result<(), String> {
+    let inputs = fs::read_to_string(&inputs_path)
+        .map_err(|why| format!("couldn't open {}: {}", inputs_path.display(), why))?;
+
+    let lines: Vec<_> = inputs
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+
+    let mut failed = vec![];
+
+    for &(i, line) in &lines {
+        let witness = zokrates_abi::parse_strict_signature(line, signature)
+            .map_err(|e| format!("Could not parse argument: {}", e))
+            .and_then(|arguments: zokrates_abi::CheckedValues<FieldPrime>| {
+                program
+                    .execute(&arguments.encode())
+                    .map_err(|e| format!("Execution failed: {}", e))
+            });
+
+        match witness {
+            Ok(witness) => {
+                let return_values: Value = zokrates_abi::CheckedValues::decode(
+                    witness.return_values(),
+                    signature.outputs.clone(),
+                )
+                .into();
+                write_witness(
+                    &witness,
+                    &return_values,
+                    &batch_output_path(output_path, i + 1),
+                    names,
+                )?;
+            }
+            Err(e) => failed.push((i + 1, e)),
+        }
+    }
+
+    println!(
+        "Computed {} of {} witnesses",
+        lines.len() - failed.len(),
+        lines.len()
+    );
+
+    match failed.len() {
+        0 => Ok(()),
+        _ => Err(failed
+            .into_iter()
+            .map(|(line, e)| format!("Line {} failed: {}", line, e))
+            .collect::<Vec<_>>()
+            .join("\n")),
+    }
+}
+
+/// Path of the output for line `line` of a batch: `witness_3` for `witness`, `out_3.txt` for `out.txt`
+fn batch_output_path(path: &Path, line: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, line, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, line),
+    };
+    path.with_file_name(name)
+}
+
+fn write_mpc_parameters(params: &MPCParameters, path: &Path) -> Result<(), String> {
+    let file = File::create(&path)
+        .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?;
+
+    params
+        .write(BufWriter::new(file))
+        .map_err(|e| format!("couldn't write {}: {}", path.display(), e))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.trim_start_matches("0x");
+
+    if s.len() % 2 != 0 {
+        return Err(format!("Invalid hex string: {}", s));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| format!("Invalid hex string: {}", s))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     extern crate glob;
@@ -695,7 +1914,7 @@ mod tests {
                 .into_string()
                 .unwrap();
 
-            let _: ir::Prog<FieldPrime> =
+            let _: CompilationArtifacts<FieldPrime> =
                 compile(&mut reader, Some(location), Some(resolve)).unwrap();
         }
     }
@@ -722,10 +1941,11 @@ mod tests {
 
             let mut reader = BufReader::new(file);
 
-            let program_flattened: ir::Prog<FieldPrime> =
+            let artifacts: CompilationArtifacts<FieldPrime> =
                 compile(&mut reader, Some(location), Some(resolve)).unwrap();
 
-            let _ = program_flattened
+            let _ = artifacts
+                .prog()
                 .execute(&vec![FieldPrime::from(0)])
                 .unwrap();
         }
@@ -754,12 +1974,45 @@ mod tests {
 
             let mut reader = BufReader::new(file);
 
-            let program_flattened: ir::Prog<FieldPrime> =
+            let artifacts: CompilationArtifacts<FieldPrime> =
                 compile(&mut reader, Some(location), Some(resolve)).unwrap();
 
-            let _ = program_flattened
+            let _ = artifacts
+                .prog()
                 .execute(&vec![FieldPrime::from(0)])
                 .unwrap();
         }
     }
+
+    #[test]
+    fn returns_paths() {
+        assert_eq!(
+            returns_path(Path::new("witness")),
+            PathBuf::from("witness.returns.json")
+        );
+        assert_eq!(
+            returns_path(Path::new("out/witness.json")),
+            PathBuf::from("out/witness.json.returns.json")
+        );
+    }
+
+    #[test]
+    fn batch_output_paths() {
+        assert_eq!(
+            batch_output_path(Path::new("witness"), 3),
+            PathBuf::from("witness_3")
+        );
+        assert_eq!(
+            batch_output_path(Path::new("out/witness.txt"), 12),
+            PathBuf::from("out/witness_12.txt")
+        );
+
+        // the return values of a witness ending in `.json` do not overwrite it
+        let witness = batch_output_path(Path::new("out/witness.json"), 2);
+        assert_eq!(witness, PathBuf::from("out/witness_2.json"));
+        assert_eq!(
+            returns_path(&witness),
+            PathBuf::from("out/witness_2.json.returns.json")
+        );
+    }
 }
@@ -0,0 +1,617 @@
+//! `zokrates serve`: keeps compiled programs and their keys in memory and answers JSON-RPC 2.0
+//! requests over HTTP to compute witnesses, generate proofs and verify them, so that each request
+//! does not have to read the program and the proving key from disk again.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use zokrates_abi::{CheckedValues, Decode, Encode};
+use zokrates_core::ir;
+use zokrates_core::proof_system::{G16ProvingKey, ProofSystem, G16};
+use zokrates_field::field::{Field, FieldPrime};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Code for requests which are well formed but fail, for example because execution failed
+const SERVER_ERROR: i64 = -32000;
+
+/// Number of connections answered concurrently, further connections wait to be accepted
+const WORKERS: usize = 4;
+/// Maximum size in bytes of the request line and headers of a request
+const MAX_HEAD_SIZE: u64 = 16 * 1024;
+/// Maximum size in bytes of the body of a request
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+/// Time after which a connection which stopped sending its request or reading its response is
+/// dropped, so that idle clients cannot hold on to the workers
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new<S: Into<String>>(code: i64, message: S) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// A compiled program, with the G16 keys to prove and verify its executions if they were found
+pub struct Program {
+    program: ir::Prog<FieldPrime>,
+    proving_key: Option<G16ProvingKey>,
+    verification_key: Option<String>,
+}
+
+impl Program {
+    pub fn new(
+        program: ir::Prog<FieldPrime>,
+        proving_key: Option<G16ProvingKey>,
+        verification_key: Option<String>,
+    ) -> Self {
+        Program {
+            program,
+            proving_key,
+            verification_key,
+        }
+    }
+
+    /// Executes the program on the ABI inputs found in `params`
+    fn compute_witness(&self, params: &Value) -> Result<ir::Witness<FieldPrime>, RpcError> {
+        let inputs = params
+            .get("inputs")
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing `inputs`"))?;
+
+        let arguments: CheckedValues<FieldPrime> =
+            zokrates_abi::parse_strict_signature(&inputs.to_string(), &self.program.signature)
+                .map_err(|e| {
+                    RpcError::new(INVALID_PARAMS, format!("Could not parse argument: {}", e))
+                })?;
+
+        self.program
+            .execute(&arguments.encode())
+            .map_err(|e| RpcError::new(SERVER_ERROR, format!("Execution failed: {}", e)))
+    }
+}
+
+pub struct Server {
+    programs: HashMap<String, Program>,
+    timeout: Duration,
+}
+
+impl Server {
+    pub fn new(programs: HashMap<String, Program>) -> Self {
+        Server {
+            programs,
+            timeout: TIMEOUT,
+        }
+    }
+
+    /// Listens for requests on `address` until the process is stopped
+    pub fn listen(self, address: &str) -> Result<(), String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|why| format!("couldn't listen on {}: {}", address, why))?;
+
+        println!("Listening on {}", address);
+
+        self.serve(listener)
+    }
+
+    /// Answers the requests made to `listener` with a pool of `WORKERS` threads
+    pub fn serve(self, listener: TcpListener) -> Result<(), String> {
+        let server = Arc::new(self);
+
+        // accepting blocks while all workers are busy and the queue is full
+        let (sender, receiver) = sync_channel::<TcpStream>(WORKERS);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for _ in 0..WORKERS {
+            let server = server.clone();
+            let receiver = receiver.clone();
+
+            thread::spawn(move || loop {
+                // the lock is released as soon as a connection is received
+                let stream = match receiver.lock().unwrap().recv() {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+
+                // a request which panics fails on its own, without taking its worker down
+                match panic::catch_unwind(AssertUnwindSafe(|| server.serve_connection(stream))) {
+                    Ok(Ok(())) => {}
+                    Ok(Err(why)) => eprintln!("Connection failed: {}", why),
+                    Err(_) => eprintln!("Connection failed: the request handler panicked"),
+                }
+            });
+        }
+
+        for stream in listener.incoming() {
+            let stream = stream.map_err(|why| format!("couldn't accept connection: {}", why))?;
+            sender
+                .send(stream)
+                .map_err(|_| String::from("couldn't serve connection: all workers stopped"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Answers a single HTTP request, whose body is a JSON-RPC request, and closes the connection
+    fn serve_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let request = match read_request(&mut reader) {
+            Err(ref why)
+                if why.kind() == io::ErrorKind::WouldBlock
+                    || why.kind() == io::ErrorKind::TimedOut =>
+            {
+                Ok(Err("408 Request Timeout"))
+            }
+            request => request,
+        }?;
+
+        let (status, body, is_rejected) = match request {
+            Ok(body) => (
+                "200 OK",
+                self.handle(&String::from_utf8_lossy(&body)).to_string(),
+                false,
+            ),
+            Err(status) => (status, String::new(), true),
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        // closing a connection with unread data resets it, possibly before the client has read
+        // the response, so a rejected request is drained for a bounded amount and time
+        if is_rejected {
+            stream.shutdown(Shutdown::Write)?;
+            stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+            let _ = io::copy(&mut reader.take(MAX_HEAD_SIZE), &mut io::sink());
+        }
+
+        Ok(())
+    }
+
+    /// Answers a JSON-RPC request
+    pub fn handle(&self, request: &str) -> Value {
+        let request: Value = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(why) => {
+                return response(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, why.to_string())),
+                )
+            }
+        };
+
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => self.call(method, request.get("params").unwrap_or(&Value::Null)),
+            None => Err(RpcError::new(INVALID_REQUEST, "Missing `method`")),
+        };
+
+        response(id, result)
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "compute_witness" => {
+                let program = self.program(params)?;
+                let witness = program.compute_witness(params)?;
+
+                let outputs: Value = CheckedValues::decode(
+                    witness.return_values(),
+                    program.program.signature.outputs.clone(),
+                )
+                .into();
+
+                let mut serialized = vec![];
+                witness
+                    .write(&mut serialized)
+                    .map_err(|why| RpcError::new(SERVER_ERROR, why.to_string()))?;
+
+                Ok(json!({
+                    "witness": String::from_utf8(serialized)
+                        .map_err(|why| RpcError::new(SERVER_ERROR, why.to_string()))?,
+                    "outputs": outputs
+                }))
+            }
+            "prove" => {
+                let program = self.program(params)?;
+                let proving_key = program.proving_key.as_ref().ok_or_else(|| {
+                    RpcError::new(SERVER_ERROR, "No proving key was loaded for this program")
+                })?;
+
+                // prove either a witness computed beforehand or the execution on the given inputs
+                let witness = match params.get("witness").and_then(Value::as_str) {
+                    Some(witness) => ir::Witness::read(witness.as_bytes()).map_err(|why| {
+                        RpcError::new(INVALID_PARAMS, format!("Could not read witness: {}", why))
+                    })?,
+                    None => program.compute_witness(params)?,
+                };
+
+                let proof = G16 {}
+                    .generate_proof_with_key(&program.program, witness, proving_key)
+                    .map_err(|e| {
+                        RpcError::new(SERVER_ERROR, format!("Generating proof failed: {}", e))
+                    })?;
+
+                serde_json::from_str(&proof)
+                    .map_err(|why| RpcError::new(SERVER_ERROR, why.to_string()))
+            }
+            "verify" => {
+                let program = self.program(params)?;
+                let verification_key = program.verification_key.as_ref().ok_or_else(|| {
+                    RpcError::new(
+                        SERVER_ERROR,
+                        "No verification key was loaded for this program",
+                    )
+                })?;
+
+                let proof = params
+                    .get("proof")
+                    .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing `proof`"))?;
+
+                // expected public inputs, as decimal strings
+                let inputs = match params.get("inputs") {
+                    Some(inputs) => Some(
+                        inputs
+                            .as_array()
+                            .and_then(|inputs| {
+                                inputs
+                                    .iter()
+                                    .map(|i| {
+                                        i.as_str()
+                                            .and_then(|i| FieldPrime::try_from_dec_str(i).ok())
+                                    })
+                                    .collect::<Option<Vec<_>>>()
+                            })
+                            .ok_or_else(|| {
+                                RpcError::new(
+                                    INVALID_PARAMS,
+                                    "Expected `inputs` to be an array of decimal strings",
+                                )
+                            })?,
+                    ),
+                    None => None,
+                };
+
+                G16 {}
+                    .verify(verification_key, &proof.to_string(), inputs)
+                    .map(Value::Bool)
+                    .map_err(|e| RpcError::new(SERVER_ERROR, format!("Verification failed: {}", e)))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method `{}` not found", method),
+            )),
+        }
+    }
+
+    /// The program named by the `program` parameter of a request
+    fn program(&self, params: &Value) -> Result<&Program, RpcError> {
+        let name = params
+            .get("program")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing `program`"))?;
+
+        self.programs
+            .get(name)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Program `{}` not found", name)))
+    }
+}
+
+/// Reads a line of the head of a request, or returns `None` if it is cut short by the end of the
+/// stream or by the size limit of the head
+fn read_line<R: BufRead>(head: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+
+    Ok(match line.ends_with('\n') {
+        true => Some(line),
+        false => None,
+    })
+}
+
+/// Reads an HTTP request and returns its body, or the status to answer with if it is rejected
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Result<Vec<u8>, &'static str>> {
+    const HEAD_TOO_LARGE: &str = "431 Request Header Fields Too Large";
+
+    let mut head = reader.by_ref().take(MAX_HEAD_SIZE);
+
+    let request_line = match read_line(&mut head)? {
+        Some(line) => line,
+        None => return Ok(Err(HEAD_TOO_LARGE)),
+    };
+
+    let mut content_length = 0;
+    loop {
+        let header = match read_line(&mut head)? {
+            Some(header) => header,
+            None => return Ok(Err(HEAD_TOO_LARGE)),
+        };
+        let header = header.trim();
+
+        if header.is_empty() {
+            break;
+        }
+
+        let mut parts = header.splitn(2, ':');
+        if parts
+            .next()
+            .unwrap()
+            .trim()
+            .eq_ignore_ascii_case("content-length")
+        {
+            content_length = match parts.next().unwrap_or("").trim().parse() {
+                Ok(content_length) => content_length,
+                Err(_) => return Ok(Err("400 Bad Request")),
+            };
+        }
+    }
+
+    if !request_line.starts_with("POST ") {
+        return Ok(Err("405 Method Not Allowed"));
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Ok(Err("413 Payload Too Large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Ok(body))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "error": { "code": e.code, "message": e.message },
+            "id": id
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_core::compile::{compile, CompilationArtifacts};
+
+    fn server() -> Server {
+        let source = r#"
+            def main(private field a, field b) -> (field):
+                field c = a * b
+                return c
+        "#;
+
+        let artifacts: CompilationArtifacts<FieldPrime> =
+            compile(&mut source.as_bytes(), None, Some(crate::resolve)).unwrap();
+        let program = artifacts.prog().clone();
+
+        let keypair = G16 {}.setup(program.clone()).unwrap();
+
+        let mut programs = HashMap::new();
+        programs.insert(
+            String::from("mul"),
+            Program::new(
+                program,
+                Some(G16 {}.read_proving_key(&keypair.pk).unwrap()),
+                Some(keypair.vk),
+            ),
+        );
+
+        Server::new(programs)
+    }
+
+    /// Sends the raw HTTP `request` to the server at `address` and returns the raw response
+    fn send(address: &str, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    /// Sends `request` to the server at `address` the way an HTTP client would
+    fn post(address: &str, request: &Value) -> Value {
+        let body = request.to_string();
+        let response = send(
+            address,
+            &format!(
+                "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                address,
+                body.len(),
+                body
+            ),
+        );
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.splitn(2, "\r\n\r\n").nth(1).unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn compute_prove_verify() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = server();
+        thread::spawn(move || server.serve(listener));
+
+        let response = post(
+            &address,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "compute_witness",
+                "params": { "program": "mul", "inputs": { "b": "4", "a": "3" } },
+                "id": 1
+            }),
+        );
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["result"]["outputs"], json!(["12"]));
+        let witness = response["result"]["witness"].clone();
+
+        let proof = post(
+            &address,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "prove",
+                "params": { "program": "mul", "witness": witness },
+                "id": 2
+            }),
+        )["result"]
+            .clone();
+
+        let response = post(
+            &address,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "verify",
+                "params": { "program": "mul", "proof": proof },
+                "id": 3
+            }),
+        );
+        assert_eq!(response["result"], json!(true));
+
+        // the public inputs are `b` and the return value
+        let response = post(
+            &address,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "verify",
+                "params": { "program": "mul", "proof": proof, "inputs": ["4", "13"] },
+                "id": 4
+            }),
+        );
+        assert_eq!(response["result"], json!(false));
+
+        // a proof can also be generated from the inputs directly
+        let proof = post(
+            &address,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "prove",
+                "params": { "program": "mul", "inputs": ["5", "6"] },
+                "id": 5
+            }),
+        )["result"]
+            .clone();
+        assert_eq!(proof["inputs"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn limits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = server();
+        thread::spawn(move || server.serve(listener));
+
+        let response = send(
+            &address,
+            "POST / HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n{}",
+        );
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+
+        let response = send(
+            &address,
+            &format!(
+                "POST / HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+                "a".repeat(MAX_HEAD_SIZE as usize)
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"));
+
+        let response = send(&address, "GET / HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        let response = send(&address, "POST / HTTP/1.1\r\nContent-Length: 2a\r\n\r\n{}");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+
+        // the server still answers requests after rejecting others
+        let response = post(
+            &address,
+            &json!({ "jsonrpc": "2.0", "method": "compute_witness", "params": { "program": "mul", "inputs": ["2", "3"] }, "id": 1 }),
+        );
+        assert_eq!(response["result"]["outputs"], json!(["6"]));
+    }
+
+    #[test]
+    fn timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = Server {
+            timeout: Duration::from_millis(500),
+            ..server()
+        };
+        thread::spawn(move || server.serve(listener));
+
+        // occupy every worker with a connection which never sends its request
+        let idle: Vec<_> = (0..WORKERS)
+            .map(|_| TcpStream::connect(&address).unwrap())
+            .collect();
+
+        let response = post(
+            &address,
+            &json!({ "jsonrpc": "2.0", "method": "compute_witness", "params": { "program": "mul", "inputs": ["2", "3"] }, "id": 1 }),
+        );
+        assert_eq!(response["result"]["outputs"], json!(["6"]));
+
+        for mut stream in idle {
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let server = server();
+
+        let code = |request: &str| server.handle(request)["error"]["code"].clone();
+
+        assert_eq!(code("{"), json!(PARSE_ERROR));
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "id": 1}"#),
+            json!(INVALID_REQUEST)
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "method": "setup", "params": {}, "id": 1}"#),
+            json!(METHOD_NOT_FOUND)
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "method": "prove", "params": {"program": "add"}, "id": 1}"#),
+            json!(INVALID_PARAMS)
+        );
+        assert_eq!(
+            code(
+                r#"{"jsonrpc": "2.0", "method": "compute_witness", "params": {"program": "mul", "inputs": {"a": "1"}}, "id": 1}"#
+            ),
+            json!(INVALID_PARAMS)
+        );
+    }
+}
@@ -4,7 +4,6 @@ extern crate serde_json;
 #[cfg(test)]
 mod integration {
     use assert_cli;
-    use bincode::{deserialize_from, Infinite};
     use std::fs;
     use std::fs::File;
     use std::io::{BufReader, Read};
@@ -13,7 +12,8 @@ mod integration {
     use tempdir::TempDir;
     use zokrates_abi::{parse_strict, Encode};
     use zokrates_core::ir;
-    use zokrates_field::field::FieldPrime;
+    use zokrates_core::typed_absy::abi::Abi;
+    use zokrates_field::field::{Field, FieldPrime};
 
     #[test]
     #[ignore]
@@ -61,8 +61,15 @@ mod integration {
         let tmp_base = tmp_dir.path();
         let test_case_path = tmp_base.join(program_name);
         let flattened_path = tmp_base.join(program_name).join("out");
+        let abi_spec_path = tmp_base.join(program_name).join("abi.json");
         let witness_path = tmp_base.join(program_name).join("witness");
         let inline_witness_path = tmp_base.join(program_name).join("inline_witness");
+        let file_witness_path = tmp_base.join(program_name).join("file_witness");
+        let named_witness_path = tmp_base.join(program_name).join("named_witness");
+        let wtns_path = tmp_base.join(program_name).join("witness.wtns");
+        let wtns_json_path = tmp_base.join(program_name).join("witness.wtns.json");
+        let trace_path = tmp_base.join(program_name).join("trace.json");
+        let named_trace_path = tmp_base.join(program_name).join("named_trace.json");
         let proof_path = tmp_base.join(program_name).join("proof.json");
         let verification_key_path = tmp_base
             .join(program_name)
@@ -88,6 +95,8 @@ mod integration {
             program_path.to_str().unwrap(),
             "-o",
             flattened_path.to_str().unwrap(),
+            "-s",
+            abi_spec_path.to_str().unwrap(),
             "--light",
         ];
 
@@ -101,14 +110,78 @@ mod integration {
             .map_err(|why| format!("couldn't open {}: {}", flattened_path.display(), why))
             .unwrap();
 
-        let mut reader = BufReader::new(file);
+        let reader = BufReader::new(file);
 
-        let ir_prog: ir::Prog<FieldPrime> = deserialize_from(&mut reader, Infinite)
+        let ir_prog: ir::Prog<FieldPrime> = ir::Prog::read(reader)
             .map_err(|why| why.to_string())
             .unwrap();
 
         let signature = ir_prog.signature.clone();
 
+        // the ABI specification written by `compile` describes the same interface
+        let abi: Abi = serde_json::from_reader(File::open(&abi_spec_path).unwrap()).unwrap();
+
+        assert_eq!(abi.signature(), signature);
+
+        // PROFILE
+
+        let folded_path = tmp_base.join(program_name).join("profile.folded");
+
+        let profile = vec![
+            "../target/release/zokrates",
+            "profile",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "--folded",
+            folded_path.to_str().unwrap(),
+        ];
+
+        assert_cli::Assert::command(&profile).succeeds().unwrap();
+
+        // every constraint is attributed to exactly one call stack
+        let profiled_constraints: usize = fs::read_to_string(&folded_path)
+            .unwrap()
+            .lines()
+            .map(|l| l.rsplit(' ').next().unwrap().parse::<usize>().unwrap())
+            .sum();
+
+        assert_eq!(profiled_constraints, ir_prog.constraint_count());
+
+        // EXPORT-R1CS
+
+        let r1cs_path = tmp_base.join(program_name).join("out.r1cs");
+        let r1cs_json_path = tmp_base.join(program_name).join("out.r1cs.json");
+
+        assert_cli::Assert::command(&[
+            "../target/release/zokrates",
+            "export-r1cs",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "-o",
+            r1cs_path.to_str().unwrap(),
+        ])
+        .succeeds()
+        .unwrap();
+
+        assert_eq!(&fs::read(&r1cs_path).unwrap()[0..4], b"r1cs");
+
+        assert_cli::Assert::command(&[
+            "../target/release/zokrates",
+            "export-r1cs",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "-o",
+            r1cs_json_path.to_str().unwrap(),
+            "--json",
+        ])
+        .succeeds()
+        .unwrap();
+
+        let r1cs: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&r1cs_json_path).unwrap()).unwrap();
+
+        assert_eq!(r1cs["nConstraints"], ir_prog.constraint_count());
+
         // run witness-computation for ABI-encoded inputs through stdin
         let json_input_str = fs::read_to_string(inputs_path).unwrap();
 
@@ -119,6 +192,8 @@ mod integration {
             flattened_path.to_str().unwrap(),
             "-o",
             witness_path.to_str().unwrap(),
+            "-s",
+            abi_spec_path.to_str().unwrap(),
             "--stdin",
             "--abi",
         ];
@@ -158,6 +233,139 @@ mod integration {
             .succeeds()
             .unwrap();
 
+        // run witness-computation for ABI-encoded inputs read from a file
+        let compute_file = vec![
+            "../target/release/zokrates",
+            "compute-witness",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "-o",
+            file_witness_path.to_str().unwrap(),
+            "-s",
+            abi_spec_path.to_str().unwrap(),
+            "--inputs",
+            inputs_path.to_str().unwrap(),
+            "--trace",
+            trace_path.to_str().unwrap(),
+        ];
+
+        assert_cli::Assert::command(&compute_file)
+            .succeeds()
+            .unwrap();
+
+        // every statement of a successful execution is traced
+        let trace: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&trace_path).unwrap()).unwrap();
+
+        assert_eq!(
+            trace["steps"].as_array().unwrap().len(),
+            ir_prog.main.statements.len()
+        );
+
+        // run witness-computation writing the names of the variables
+        let compute_named = vec![
+            "../target/release/zokrates",
+            "compute-witness",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "-o",
+            named_witness_path.to_str().unwrap(),
+            "-s",
+            abi_spec_path.to_str().unwrap(),
+            "--inputs",
+            inputs_path.to_str().unwrap(),
+            "--names",
+            "--wtns",
+            wtns_path.to_str().unwrap(),
+            "--wtns-json",
+            wtns_json_path.to_str().unwrap(),
+            "--trace",
+            named_trace_path.to_str().unwrap(),
+            "--light",
+        ];
+
+        assert_cli::Assert::command(&compute_named)
+            .succeeds()
+            .unwrap();
+
+        // the variables of the trace are named after the source code where known
+        let names = ir_prog.names();
+        let named_trace: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&named_trace_path).unwrap()).unwrap();
+
+        for (step, named_step) in trace["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(named_trace["steps"].as_array().unwrap())
+        {
+            if let Some(variable) = step["variable"].as_str() {
+                let name = names
+                    .iter()
+                    .find(|(v, _)| v.to_string() == variable)
+                    .map(|(_, name)| name.as_str())
+                    .unwrap_or(variable);
+                assert_eq!(named_step["variable"], name);
+            }
+        }
+
+        // the names are ignored when reading the witness back
+        assert_eq!(
+            ir::Witness::<FieldPrime>::read(File::open(&named_witness_path).unwrap()).unwrap(),
+            ir::Witness::<FieldPrime>::read(File::open(&file_witness_path).unwrap()).unwrap()
+        );
+
+        // the wires of the constraint system are written in the same order in both formats
+        let wires = ir_prog.r1cs().wires;
+        let wtns =
+            ir::Witness::<FieldPrime>::read_wtns(&wires, File::open(&wtns_path).unwrap()).unwrap();
+        let wtns_json: Vec<String> =
+            serde_json::from_str(&fs::read_to_string(&wtns_json_path).unwrap()).unwrap();
+
+        assert_eq!(
+            wires
+                .iter()
+                .map(|w| wtns.0.get(w).unwrap().to_dec_string())
+                .collect::<Vec<_>>(),
+            wtns_json
+        );
+
+        // IMPORT-R1CS
+
+        let imported_path = tmp_base.join(program_name).join("imported");
+        let imported_witness_path = tmp_base.join(program_name).join("imported_witness");
+
+        assert_cli::Assert::command(&[
+            "../target/release/zokrates",
+            "import-r1cs",
+            "-i",
+            r1cs_path.to_str().unwrap(),
+            "-o",
+            imported_path.to_str().unwrap(),
+            "--wtns",
+            wtns_path.to_str().unwrap(),
+            "-w",
+            imported_witness_path.to_str().unwrap(),
+        ])
+        .succeeds()
+        .unwrap();
+
+        // the imported program has the same constraints, which the imported witness satisfies
+        let imported: ir::Prog<FieldPrime> =
+            ir::Prog::read(BufReader::new(File::open(&imported_path).unwrap())).unwrap();
+        let imported_witness =
+            ir::Witness::read(File::open(&imported_witness_path).unwrap()).unwrap();
+
+        assert_eq!(imported.constraint_count(), ir_prog.constraint_count());
+        assert!(imported.check_witness(&imported_witness).is_ok());
+
+        // the return values are written next to the witness
+        assert_eq!(
+            fs::read_to_string(file_witness_path.with_file_name("file_witness.returns.json"))
+                .unwrap(),
+            fs::read_to_string(witness_path.with_file_name("witness.returns.json")).unwrap()
+        );
+
         // load the expected witness
         let mut expected_witness_file = File::open(&expected_witness_path).unwrap();
         let mut expected_witness = String::new();
@@ -178,6 +386,7 @@ mod integration {
             .unwrap();
 
         assert_eq!(inline_witness, witness);
+        assert_eq!(fs::read_to_string(&file_witness_path).unwrap(), witness);
 
         for line in expected_witness.as_str().split("\n") {
             assert!(
@@ -224,19 +433,65 @@ mod integration {
             .succeeds()
             .unwrap();
 
-            // EXPORT-VERIFIER (AMV)
+            // EXPORT-AVM-VERIFIER
+            let avm_verifier_path = tmp_base
+                .join(program_name)
+                .join(format!("avm-verifier-{}", scheme));
+
+            assert_cli::Assert::command(&[
+                "../target/release/zokrates",
+                "export-avm-verifier",
+                "-i",
+                verification_key_path.to_str().unwrap(),
+                "-o",
+                avm_verifier_path.to_str().unwrap(),
+                "--proving-scheme",
+                scheme,
+                "--package",
+                "org.example.zk",
+                "--class",
+                "MyVerifier",
+                "--prefix",
+                "My",
+            ])
+            .succeeds()
+            .unwrap();
+
+            let avm_verifier =
+                fs::read_to_string(avm_verifier_path.join("MyVerifier.java")).unwrap();
+            assert!(avm_verifier.contains("package org.example.zk;"));
+            assert!(avm_verifier.contains("public class MyVerifier {"));
+            assert!(avm_verifier_path.join("MyPairing.java").exists());
+
+            // EXPORT-AVM-VERIFIER (registry)
+            let avm_registry_path = tmp_base
+                .join(program_name)
+                .join(format!("avm-registry-{}", scheme));
+
             assert_cli::Assert::command(&[
                 "../target/release/zokrates",
-                "export-verifier-avm",
+                "export-avm-verifier",
+                "-i",
+                verification_key_path.to_str().unwrap(),
                 "-i",
                 verification_key_path.to_str().unwrap(),
                 "-o",
-                verification_contract_path.to_str().unwrap(),
+                avm_registry_path.to_str().unwrap(),
                 "--proving-scheme",
                 scheme,
             ])
-                .succeeds()
+            .succeeds()
+            .unwrap();
+
+            let avm_registry = fs::read_to_string(avm_registry_path.join("Verifier.java")).unwrap();
+            assert!(avm_registry.contains("public static final int CIRCUIT_COUNT = 2;"));
+            for id in 0..2 {
+                let circuit = fs::read_to_string(
+                    avm_registry_path.join(format!("VerifierCircuit{}.java", id)),
+                )
                 .unwrap();
+                assert!(!circuit.contains("@Callable"));
+            }
 
             // GENERATE-PROOF
             assert_cli::Assert::command(&[
@@ -256,6 +511,62 @@ mod integration {
             .succeeds()
             .unwrap();
 
+            // VERIFY
+            assert_cli::Assert::command(&[
+                "../target/release/zokrates",
+                "verify",
+                "-v",
+                verification_key_path.to_str().unwrap(),
+                "-j",
+                proof_path.to_str().unwrap(),
+                "--proving-scheme",
+                scheme,
+            ])
+            .succeeds()
+            .unwrap();
+
+            // PRINT-PROOF (AVM)
+            assert_cli::Assert::command(&[
+                "../target/release/zokrates",
+                "print-proof",
+                "-j",
+                proof_path.to_str().unwrap(),
+                "--format",
+                "avm",
+                "--proving-scheme",
+                scheme,
+                "--call-data",
+            ])
+            .succeeds()
+            .stdout()
+            .contains("call data: 0x210006766572696679")
+            .unwrap();
+
+            // EXPORT-AVM-VERIFIER (with test)
+            let avm_test_path = tmp_base
+                .join(program_name)
+                .join(format!("avm-verifier-test-{}", scheme));
+
+            assert_cli::Assert::command(&[
+                "../target/release/zokrates",
+                "export-avm-verifier",
+                "-i",
+                verification_key_path.to_str().unwrap(),
+                "-o",
+                avm_test_path.to_str().unwrap(),
+                "--proving-scheme",
+                scheme,
+                "--proof",
+                proof_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .unwrap();
+
+            let avm_test = fs::read_to_string(avm_test_path.join("VerifierTest.java")).unwrap();
+            assert!(
+                avm_test.contains("private static final String CALL_DATA_0 = \"210006766572696679")
+            );
+
             // TEST VERIFIER
 
             assert_cli::Assert::command(&[
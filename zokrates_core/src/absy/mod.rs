@@ -271,7 +271,11 @@ impl<'ast, T: Field> fmt::Debug for Assignee<'ast, T> {
 
 impl<'ast, T: Field> fmt::Display for Assignee<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match *self {
+            Assignee::Identifier(ref s) => write!(f, "{}", s),
+            Assignee::Select(ref a, ref e) => write!(f, "{}[{}]", a, e),
+            Assignee::Member(ref s, ref m) => write!(f, "{}.{}", s, m),
+        }
     }
 }
 
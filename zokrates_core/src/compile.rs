@@ -14,10 +14,28 @@ use std::collections::HashMap;
 use std::fmt;
 use std::io;
 use std::io::BufRead;
+use typed_absy::abi::Abi;
 use typed_arena::Arena;
 use zokrates_field::field::Field;
 use zokrates_pest_ast as pest;
 
+/// The result of a successful compilation: the program and the interface of its `main` function
+#[derive(Debug)]
+pub struct CompilationArtifacts<T: Field> {
+    prog: ir::Prog<T>,
+    abi: Abi,
+}
+
+impl<T: Field> CompilationArtifacts<T> {
+    pub fn prog(&self) -> &ir::Prog<T> {
+        &self.prog
+    }
+
+    pub fn abi(&self) -> &Abi {
+        &self.abi
+    }
+}
+
 #[derive(Debug)]
 pub struct CompileErrors(Vec<CompileError>);
 
@@ -130,7 +148,7 @@ pub fn compile<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     reader: &mut R,
     location: Option<String>,
     resolve_option: Option<Resolve<S, E>>,
-) -> Result<ir::Prog<T>, CompileErrors> {
+) -> Result<CompilationArtifacts<T>, CompileErrors> {
     let arena = Arena::new();
 
     let mut source = String::new();
@@ -150,6 +168,8 @@ pub fn compile<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
         )
     })?;
 
+    let abi = typed_ast.abi();
+
     // analyse (unroll and constant propagation)
     let typed_ast = typed_ast.analyse();
 
@@ -165,7 +185,10 @@ pub fn compile<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     // optimize
     let optimized_ir_prog = ir_prog.optimize();
 
-    Ok(optimized_ir_prog)
+    Ok(CompilationArtifacts {
+        prog: optimized_ir_prog,
+        abi,
+    })
 }
 
 pub fn compile_program<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
@@ -218,6 +241,8 @@ pub fn compile_module<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
 mod test {
     use super::*;
     use std::io::{BufReader, Empty};
+    use typed_absy::abi::{AbiInput, AbiOutput};
+    use typed_absy::Type;
     use zokrates_field::field::FieldPrime;
 
     #[test]
@@ -230,7 +255,7 @@ mod test {
 		"#
             .as_bytes(),
         );
-        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+        let res: Result<CompilationArtifacts<FieldPrime>, CompileErrors> = compile(
             &mut r,
             Some(String::from("./path/to/file")),
             None::<Resolve<BufReader<Empty>, io::Error>>,
@@ -251,11 +276,88 @@ mod test {
 		"#
             .as_bytes(),
         );
-        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+        let res: Result<CompilationArtifacts<FieldPrime>, CompileErrors> = compile(
             &mut r,
             Some(String::from("./path/to/file")),
             None::<Resolve<BufReader<Empty>, io::Error>>,
         );
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn abi() {
+        let mut r = BufReader::new(
+            r#"
+			struct Point {
+			   field x
+			   field y
+			}
+			def main(Point p, private bool[2] flags) -> (field):
+			   return p.x
+		"#
+            .as_bytes(),
+        );
+        let artifacts: CompilationArtifacts<FieldPrime> = compile(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<Resolve<BufReader<Empty>, io::Error>>,
+        )
+        .unwrap();
+
+        assert_eq!(
+            artifacts.abi(),
+            &Abi {
+                inputs: vec![
+                    AbiInput {
+                        name: String::from("p"),
+                        public: true,
+                        ty: Type::Struct(vec![
+                            (String::from("x"), Type::FieldElement),
+                            (String::from("y"), Type::FieldElement)
+                        ])
+                    },
+                    AbiInput {
+                        name: String::from("flags"),
+                        public: false,
+                        ty: Type::array(Type::Boolean, 2)
+                    }
+                ],
+                outputs: vec![AbiOutput {
+                    ty: Type::FieldElement
+                }]
+            }
+        );
+        assert_eq!(artifacts.abi().signature(), artifacts.prog().signature);
+    }
+
+    #[test]
+    fn execution_error_location() {
+        let mut r = BufReader::new(
+            r#"
+			def foo(field a) -> (field):
+			   a == 1
+			   return a
+			def main(field a) -> (field):
+			   field b = foo(a)
+			   return b
+		"#
+            .as_bytes(),
+        );
+        let artifacts: CompilationArtifacts<FieldPrime> = compile(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<Resolve<BufReader<Empty>, io::Error>>,
+        )
+        .unwrap();
+
+        let error = artifacts
+            .prog()
+            .execute(&vec![FieldPrime::from(2)])
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.starts_with("Expected "));
+        assert!(error.contains("in `a == 1` at ./path/to/file:3:"));
+        assert!(error.contains(", called from ./path/to/file:6:"));
+    }
 }
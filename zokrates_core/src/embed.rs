@@ -4,7 +4,7 @@ use flat_absy::{
     FlatExpression, FlatExpressionList, FlatFunction, FlatParameter, FlatStatement, FlatVariable,
 };
 use reduce::Reduce;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use typed_absy::types::{FunctionKey, Signature, Type};
 use zokrates_embed::{generate_sha256_round_constraints, BellmanConstraint};
 use zokrates_field::field::Field;
@@ -130,6 +130,7 @@ pub fn sha256_round<T: Field>() -> FlatFunction<T> {
             Type::array(Type::FieldElement, current_hash_indices.len()),
         ],
         outputs: vec![Type::array(Type::FieldElement, output_indices.len())],
+        parameters: vec![],
     };
 
     // define parameters to the function based on the variables
@@ -191,6 +192,7 @@ pub fn sha256_round<T: Field>() -> FlatFunction<T> {
         arguments,
         statements,
         signature,
+        names: BTreeMap::new(),
     }
 }
 
@@ -238,6 +240,7 @@ pub fn unpack<T: Field>() -> FlatFunction<T> {
     let signature = Signature {
         inputs: vec![Type::FieldElement],
         outputs: vec![Type::array(Type::FieldElement, nbits)],
+        parameters: vec![],
     };
 
     let outputs = directive_outputs
@@ -296,6 +299,7 @@ pub fn unpack<T: Field>() -> FlatFunction<T> {
         arguments,
         statements,
         signature,
+        names: BTreeMap::new(),
     }
 }
 
@@ -13,7 +13,8 @@ pub use self::flat_variable::FlatVariable;
 
 use crate::helpers::DirectiveStatement;
 use crate::typed_absy::types::Signature;
-use std::collections::HashMap;
+use crate::typed_absy::DebugMarker;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use zokrates_field::field::Field;
 
@@ -43,6 +44,8 @@ pub struct FlatFunction<T: Field> {
     pub statements: Vec<FlatStatement<T>>,
     /// Typed signature
     pub signature: Signature,
+    /// Names of the variables bound to an identifier of the source code
+    pub names: BTreeMap<FlatVariable, String>,
 }
 
 impl<T: Field> fmt::Display for FlatFunction<T> {
@@ -96,6 +99,7 @@ pub enum FlatStatement<T: Field> {
     Condition(FlatExpression<T>, FlatExpression<T>),
     Definition(FlatVariable, FlatExpression<T>),
     Directive(DirectiveStatement<T>),
+    Debug(DebugMarker),
 }
 
 impl<T: Field> fmt::Display for FlatStatement<T> {
@@ -105,6 +109,7 @@ impl<T: Field> fmt::Display for FlatStatement<T> {
             FlatStatement::Return(ref expr) => write!(f, "return {}", expr),
             FlatStatement::Condition(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
             FlatStatement::Directive(ref d) => write!(f, "{}", d),
+            FlatStatement::Debug(ref marker) => write!(f, "{}", marker),
         }
     }
 }
@@ -118,6 +123,7 @@ impl<T: Field> fmt::Debug for FlatStatement<T> {
                 write!(f, "FlatCondition({:?}, {:?})", lhs, rhs)
             }
             FlatStatement::Directive(ref d) => write!(f, "{:?}", d),
+            FlatStatement::Debug(ref marker) => write!(f, "FlatDebug({:?})", marker),
         }
     }
 }
@@ -155,6 +161,7 @@ impl<T: Field> FlatStatement<T> {
                     ..d
                 })
             }
+            FlatStatement::Debug(marker) => FlatStatement::Debug(marker),
         }
     }
 }
@@ -7,9 +7,11 @@
 
 use crate::flat_absy::*;
 use crate::helpers::{DirectiveStatement, Helper, RustHelper};
-use crate::typed_absy::types::{FunctionIdentifier, FunctionKey, MemberId, Signature, Type};
+use crate::typed_absy::types::{
+    FunctionIdentifier, FunctionKey, MemberId, Signature, SignatureParameter, Type,
+};
 use crate::typed_absy::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use zokrates_field::field::Field;
 
@@ -20,6 +22,8 @@ pub struct Flattener<'ast, T: Field> {
     next_var_idx: usize,
     /// `FlatVariable`s corresponding to each `Identifier`
     layout: HashMap<Identifier<'ast>, Vec<FlatVariable>>,
+    /// Debug names of the `FlatVariable`s in the layout
+    names: BTreeMap<FlatVariable, String>,
     /// Cached `FlatFunction`s to avoid re-flattening them
     flat_cache: HashMap<FunctionKey<'ast>, FlatFunction<T>>,
 }
@@ -114,6 +118,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         Flattener {
             next_var_idx: 0,
             layout: HashMap::new(),
+            names: BTreeMap::new(),
             flat_cache: HashMap::new(),
         }
     }
@@ -937,6 +942,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                         inputs: new_inputs,
                     })
                 }
+                FlatStatement::Debug(marker) => FlatStatement::Debug(marker),
             })
             .collect();
 
@@ -1510,6 +1516,9 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                     }
                 }
             }
+            TypedStatement::Debug(marker) => {
+                statements_flattened.push(FlatStatement::Debug(marker))
+            }
         }
     }
 
@@ -1540,6 +1549,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         funct: TypedFunction<'ast, T>,
     ) -> FlatFunction<T> {
         self.layout = HashMap::new();
+        self.names = BTreeMap::new();
 
         self.next_var_idx = 0;
         let mut statements_flattened: Vec<FlatStatement<T>> = Vec::new();
@@ -1560,6 +1570,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
             arguments: arguments_flattened,
             statements: statements_flattened,
             signature: funct.signature,
+            names: std::mem::replace(&mut self.names, BTreeMap::new()),
         }
     }
 
@@ -1582,7 +1593,21 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         let symbols = &main_module.functions;
 
         let main_flattened = match main {
-            TypedFunctionSymbol::Here(f) => self.flatten_function(&symbols, f),
+            TypedFunctionSymbol::Here(f) => {
+                // keep the names and visibility of the parameters in the signature of the program
+                let parameters = f
+                    .arguments
+                    .iter()
+                    .map(|p| SignatureParameter {
+                        name: p.id.id.id.to_string(),
+                        public: !p.private,
+                    })
+                    .collect();
+
+                let mut main_flattened = self.flatten_function(&symbols, f);
+                main_flattened.signature = main_flattened.signature.parameters(parameters);
+                main_flattened
+            }
             _ => unreachable!("main should be a typed function locally"),
         };
 
@@ -1596,8 +1621,14 @@ impl<'ast, T: Field> Flattener<'ast, T> {
     ///
     /// * `name` - a String that holds the name of the variable
     fn use_variable(&mut self, variable: &Variable<'ast>) -> Vec<FlatVariable> {
-        let vars = self.issue_new_variables(variable.get_type().get_primitive_count());
+        let ty = variable.get_type();
+        let vars = self.issue_new_variables(ty.get_primitive_count());
 
+        self.names.extend(
+            vars.iter()
+                .cloned()
+                .zip(debug_names(debug_name(&variable.id), &ty)),
+        );
         self.layout.insert(variable.id.clone(), vars.clone());
         vars
     }
@@ -1645,6 +1676,36 @@ impl<'ast, T: Field> Flattener<'ast, T> {
     }
 }
 
+/// The name of an identifier in the debug information: the source identifier, prefixed by the
+/// inlined calls it belongs to and suffixed by its SSA version, e.g. `foo#2/a#1`
+fn debug_name(id: &Identifier) -> String {
+    let calls: String = id
+        .stack
+        .iter()
+        .map(|(_, key, count)| format!("{}#{}/", key.id, count))
+        .collect();
+
+    match id.version {
+        0 => format!("{}{}", calls, id.id),
+        version => format!("{}{}#{}", calls, id.id, version),
+    }
+}
+
+/// The names of the primitive elements of a value of type `ty` called `name`, in the order of the
+/// layout, e.g. `a[0].b`
+fn debug_names(name: String, ty: &Type) -> Vec<String> {
+    match ty {
+        Type::FieldElement | Type::Boolean => vec![name],
+        Type::Array(ty, size) => (0..*size)
+            .flat_map(|i| debug_names(format!("{}[{}]", name, i), ty))
+            .collect(),
+        Type::Struct(members) => members
+            .iter()
+            .flat_map(|(id, ty)| debug_names(format!("{}.{}", name, id), ty))
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1683,6 +1744,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -1704,6 +1766,12 @@ mod tests {
                 }),
             ],
             signature: Signature::new().outputs(vec![Type::FieldElement]),
+            names: vec![
+                (FlatVariable::new(0), String::from("a")),
+                (FlatVariable::new(1), String::from("b")),
+            ]
+            .into_iter()
+            .collect(),
         };
 
         let flattened = flattener.flatten_function(&mut HashMap::new(), function);
@@ -1743,6 +1811,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -1771,6 +1840,12 @@ mod tests {
                 }),
             ],
             signature: Signature::new().outputs(vec![Type::FieldElement]),
+            names: vec![
+                (FlatVariable::new(0), String::from("a")),
+                (FlatVariable::new(2), String::from("b")),
+            ]
+            .into_iter()
+            .collect(),
         };
 
         let flattened = flattener.flatten_function(&mut HashMap::new(), function);
@@ -1828,6 +1903,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -1891,6 +1967,12 @@ mod tests {
                 }),
             ],
             signature: Signature::new().outputs(vec![Type::FieldElement]),
+            names: vec![
+                (FlatVariable::new(0), String::from("a")),
+                (FlatVariable::new(7), String::from("b")),
+            ]
+            .into_iter()
+            .collect(),
         };
 
         let flattened = flattener.flatten_function(&mut HashMap::new(), function);
@@ -2415,4 +2497,39 @@ mod tests {
             Some(&vec![FlatVariable::new(2)])
         );
     }
+
+    #[test]
+    fn debug_names() {
+        let mut flattener: Flattener<FieldPrime> = Flattener::new();
+
+        // a field element, in `main`
+        let a = flattener.use_variable(&Variable::field_element("a".into()));
+
+        // an array of two structs, in the second call to `foo`, after SSA
+        let b = flattener.use_variable(&Variable::array(
+            Identifier::from("b").version(3).stack(vec![(
+                String::from("main"),
+                FunctionKey::with_id("foo"),
+                2,
+            )]),
+            Type::Struct(vec![
+                (String::from("x"), Type::FieldElement),
+                (String::from("y"), Type::Boolean),
+            ]),
+            2,
+        ));
+
+        let name = |v: &FlatVariable| flattener.names.get(v).unwrap().as_str();
+
+        assert_eq!(name(&a[0]), "a");
+        assert_eq!(
+            b.iter().map(name).collect::<Vec<_>>(),
+            vec![
+                "foo#2/b#3[0].x",
+                "foo#2/b#3[0].y",
+                "foo#2/b#3[1].x",
+                "foo#2/b#3[1].y"
+            ]
+        );
+    }
 }
@@ -50,10 +50,15 @@ impl<T: Field> Executable<T> for RustHelper {
                         res.push(T::zero());
                     }
                 }
-                assert_eq!(num, T::zero());
+                if num != T::zero() {
+                    return Err(format!("{} does not fit in {} bits", inputs[0], bits));
+                }
                 Ok(res)
             }
-            RustHelper::Div => Ok(vec![inputs[0].clone() / inputs[1].clone()]),
+            RustHelper::Div => match inputs[1].is_zero() {
+                true => Err(String::from("division by zero")),
+                false => Ok(vec![inputs[0].clone() / inputs[1].clone()]),
+            },
             RustHelper::Sha256Round => {
                 let i = &inputs[0..512];
                 let h = &inputs[512..];
@@ -84,6 +89,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn div_by_zero() {
+        let inputs = vec![FieldPrime::from(42), FieldPrime::from(0)];
+        assert_eq!(
+            RustHelper::Div.execute(&inputs),
+            Err(String::from("division by zero"))
+        );
+    }
+
     #[test]
     fn bits_of_42() {
         let inputs = vec![FieldPrime::from(42)];
@@ -0,0 +1,250 @@
+//! Debug information attached to the IR, to trace its statements back to the source code
+
+use crate::flat_absy::FlatVariable;
+use crate::typed_absy::{DebugMarker, SourceSpan};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Where a statement comes from: the span it was compiled from, the function this span is in, and
+/// the calls it was inlined through, outermost first
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub span: SourceSpan,
+    pub function: String,
+    pub call_stack: Vec<Call>,
+}
+
+/// A function call which was inlined
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct Call {
+    /// The span of the call
+    pub span: SourceSpan,
+    /// The function the call is in
+    pub function: String,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.span)?;
+        for call in self.call_stack.iter().rev() {
+            write!(f, ", called from {}", call.span)?;
+        }
+        Ok(())
+    }
+}
+
+impl Location {
+    /// Describes the location for an error message, e.g. "in `a == b` at ./foo.zok:3:5"
+    pub fn describe(&self) -> String {
+        format!("in `{}` at {}", self.span.text, self)
+    }
+}
+
+/// The locations of the statements of a function, and the names of its variables
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct DebugInfo {
+    /// The distinct locations of the statements
+    pub locations: Vec<Location>,
+    /// For each statement, in order, the index of its location in `locations` if it is known
+    pub statements: Vec<Option<usize>>,
+    /// The names of the variables bound to an identifier of the source code, e.g. `a#1[0].b`
+    pub names: BTreeMap<FlatVariable, String>,
+}
+
+impl DebugInfo {
+    /// The location of the statement at index `statement`
+    pub fn location(&self, statement: usize) -> Option<&Location> {
+        self.statements
+            .get(statement)
+            .and_then(|index| *index)
+            .map(|index| &self.locations[index])
+    }
+
+    /// Replaces the variables found in `text`, as printed in the IR, by their names where known
+    pub fn rename(&self, text: &str) -> String {
+        let mut res = String::with_capacity(text.len());
+        let mut token = String::new();
+
+        for c in text.chars().map(Some).chain(std::iter::once(None)) {
+            match c {
+                Some(c) if c.is_alphanumeric() || c == '_' || c == '~' => token.push(c),
+                _ => {
+                    let name = match token.starts_with('_') {
+                        true => FlatVariable::try_from_human_readable(&token)
+                            .ok()
+                            .and_then(|v| self.names.get(&v)),
+                        false => None,
+                    };
+                    res.push_str(name.unwrap_or(&token));
+                    token.clear();
+                    res.extend(c);
+                }
+            }
+        }
+
+        res
+    }
+}
+
+/// Follows the `DebugMarker`s found between statements to build the `DebugInfo` of a function
+pub struct LocationTracker {
+    current: Option<SourceSpan>,
+    function: String,
+    call_stack: Vec<(Option<SourceSpan>, String)>,
+    indices: HashMap<Location, usize>,
+    info: DebugInfo,
+}
+
+impl LocationTracker {
+    /// Creates a tracker for the statements of the function `function`
+    pub fn new(function: &str) -> Self {
+        LocationTracker {
+            current: None,
+            function: function.to_string(),
+            call_stack: vec![],
+            indices: HashMap::new(),
+            info: DebugInfo::default(),
+        }
+    }
+
+    pub fn apply(&mut self, marker: DebugMarker) {
+        match marker {
+            DebugMarker::Location(span) => self.current = Some(span),
+            DebugMarker::EnterCall(function) => {
+                let caller = std::mem::replace(&mut self.function, function);
+                self.call_stack.push((self.current.clone(), caller));
+            }
+            DebugMarker::ExitCall => {
+                if let Some((span, function)) = self.call_stack.pop() {
+                    self.current = span;
+                    self.function = function;
+                }
+            }
+        }
+    }
+
+    /// Records that the next statement comes from the current location
+    pub fn push_statement(&mut self) {
+        let function = &self.function;
+        let call_stack = &self.call_stack;
+        let indices = &mut self.indices;
+        let locations = &mut self.info.locations;
+
+        let index = self.current.clone().map(|span| {
+            let location = Location {
+                span,
+                function: function.clone(),
+                call_stack: call_stack
+                    .iter()
+                    .filter_map(|(span, function)| {
+                        span.clone().map(|span| Call {
+                            span,
+                            function: function.clone(),
+                        })
+                    })
+                    .collect(),
+            };
+
+            *indices.entry(location.clone()).or_insert_with(|| {
+                locations.push(location);
+                locations.len() - 1
+            })
+        });
+
+        self.info.statements.push(index);
+    }
+
+    /// The debug information gathered along with the names of the variables, if any statement
+    /// has a known location or any variable has a name
+    pub fn debug_info(self, names: BTreeMap<FlatVariable, String>) -> Option<DebugInfo> {
+        match self.info.locations.is_empty() && names.is_empty() {
+            true => None,
+            false => Some(DebugInfo { names, ..self.info }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Position;
+
+    fn span(module: &str, line: usize) -> SourceSpan {
+        SourceSpan {
+            module: String::from(module),
+            start: Position { line, col: 1 },
+            end: Position { line, col: 10 },
+            text: String::from("a == b"),
+        }
+    }
+
+    #[test]
+    fn track_inlined_calls() {
+        let mut tracker = LocationTracker::new("main");
+
+        // a statement before any location is not known
+        tracker.push_statement();
+
+        tracker.apply(DebugMarker::Location(span("main", 3)));
+        tracker.push_statement();
+        tracker.apply(DebugMarker::EnterCall(String::from("foo")));
+        tracker.apply(DebugMarker::Location(span("foo", 7)));
+        tracker.push_statement();
+        tracker.push_statement();
+        tracker.apply(DebugMarker::ExitCall);
+        tracker.push_statement();
+
+        let info = tracker.debug_info(BTreeMap::new()).unwrap();
+
+        assert_eq!(
+            info.statements,
+            vec![None, Some(0), Some(1), Some(1), Some(0)]
+        );
+        assert_eq!(info.location(0), None);
+        assert_eq!(
+            info.location(2),
+            Some(&Location {
+                span: span("foo", 7),
+                function: String::from("foo"),
+                call_stack: vec![Call {
+                    span: span("main", 3),
+                    function: String::from("main")
+                }]
+            })
+        );
+        assert_eq!(
+            info.location(2).unwrap().to_string(),
+            "foo:7:1, called from main:3:1"
+        );
+        assert_eq!(info.location(4).unwrap().to_string(), "main:3:1");
+    }
+
+    #[test]
+    fn no_location() {
+        let mut tracker = LocationTracker::new("main");
+        tracker.push_statement();
+        assert_eq!(tracker.debug_info(BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn rename() {
+        let info = DebugInfo {
+            names: vec![
+                (FlatVariable::new(0), String::from("a")),
+                (FlatVariable::new(12), String::from("foo#1/b#2[3]")),
+            ]
+            .into_iter()
+            .collect(),
+            ..DebugInfo::default()
+        };
+
+        assert_eq!(
+            info.rename("(1 * _0 + 2 * _12) * (1 * _1) == 1 * ~out_0"),
+            "(1 * a + 2 * foo#1/b#2[3]) * (1 * _1) == 1 * ~out_0"
+        );
+        assert_eq!(
+            info.rename("# _12 = Rust::Bits(1 * _0)"),
+            "# foo#1/b#2[3] = Rust::Bits(1 * a)"
+        );
+    }
+}
@@ -86,22 +86,51 @@ pub fn fold_directive<T: Field, F: Folder<T>>(f: &mut F, ds: Directive<T>) -> Di
 }
 
 pub fn fold_function<T: Field, F: Folder<T>>(f: &mut F, fun: Function<T>) -> Function<T> {
+    let arguments = fun
+        .arguments
+        .into_iter()
+        .map(|a| f.fold_argument(a))
+        .collect();
+
+    // a statement can be folded to any number of statements, which all keep its location. The
+    // locations may be missing for the last statements, which are then kept without one
+    let (statements, debug) = match fun.debug {
+        Some(debug) => {
+            let (statements, locations): (Vec<_>, Vec<_>) = fun
+                .statements
+                .into_iter()
+                .zip(debug.statements.into_iter().chain(std::iter::repeat(None)))
+                .flat_map(|(s, location)| {
+                    f.fold_statement(s).into_iter().map(move |s| (s, location))
+                })
+                .unzip();
+
+            (
+                statements,
+                Some(DebugInfo {
+                    statements: locations,
+                    ..debug
+                }),
+            )
+        }
+        None => (
+            fun.statements
+                .into_iter()
+                .flat_map(|s| f.fold_statement(s))
+                .collect(),
+            None,
+        ),
+    };
+
     Function {
-        arguments: fun
-            .arguments
-            .into_iter()
-            .map(|a| f.fold_argument(a))
-            .collect(),
-        statements: fun
-            .statements
-            .into_iter()
-            .flat_map(|s| f.fold_statement(s))
-            .collect(),
+        arguments,
+        statements,
         returns: fun
             .returns
             .into_iter()
             .map(|v| f.fold_variable(v))
             .collect(),
+        debug,
         ..fun
     }
 }
@@ -113,3 +142,35 @@ pub fn fold_argument<T: Field, F: Folder<T>>(f: &mut F, a: FlatVariable) -> Flat
 pub fn fold_variable<T: Field, F: Folder<T>>(_f: &mut F, v: FlatVariable) -> FlatVariable {
     v
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::field::FieldPrime;
+
+    struct Identity;
+
+    impl<T: Field> Folder<T> for Identity {}
+
+    #[test]
+    fn keep_statements_without_location() {
+        let statement =
+            Statement::definition(FlatVariable::new(1), LinComb::from(FlatVariable::new(0)));
+
+        let fun: Function<FieldPrime> = Function {
+            id: String::from("main"),
+            statements: vec![statement.clone(), statement.clone()],
+            arguments: vec![FlatVariable::new(0)],
+            returns: vec![FlatVariable::new(1)],
+            debug: Some(DebugInfo {
+                statements: vec![None],
+                ..DebugInfo::default()
+            }),
+        };
+
+        let folded = Identity.fold_function(fun);
+
+        assert_eq!(folded.statements, vec![statement.clone(), statement]);
+        assert_eq!(folded.debug.unwrap().statements, vec![None, None]);
+    }
+}
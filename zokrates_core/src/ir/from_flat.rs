@@ -1,5 +1,6 @@
 use crate::flat_absy::{FlatExpression, FlatFunction, FlatProg, FlatStatement, FlatVariable};
 use crate::helpers;
+use crate::ir::debug::LocationTracker;
 use crate::ir::{Directive, Function, LinComb, Prog, QuadComb, Statement};
 use num::Zero;
 use zokrates_field::field::Field;
@@ -15,6 +16,31 @@ impl<T: Field> From<FlatFunction<T>> for Function<T> {
             })
             .next()
             .unwrap();
+
+        // follow the debug markers to find where each statement comes from
+        let mut tracker = LocationTracker::new("main");
+        let mut statements = vec![];
+
+        for s in flat_function.statements {
+            match s {
+                FlatStatement::Return(..) => {}
+                FlatStatement::Debug(marker) => tracker.apply(marker),
+                s => {
+                    tracker.push_statement();
+                    statements.push(s.into());
+                }
+            }
+        }
+
+        // the return statement comes last, so the current location is the one of the return
+        for (index, expression) in return_expressions.iter().enumerate() {
+            tracker.push_statement();
+            statements.push(Statement::Constraint(
+                QuadComb::from_flat_expression(expression.clone()),
+                FlatVariable::public(index).into(),
+            ));
+        }
+
         Function {
             id: String::from("main"),
             arguments: flat_function.arguments.into_iter().map(|p| p.id).collect(),
@@ -23,25 +49,8 @@ impl<T: Field> From<FlatFunction<T>> for Function<T> {
                 .enumerate()
                 .map(|(index, _)| FlatVariable::public(index))
                 .collect(),
-            statements: flat_function
-                .statements
-                .into_iter()
-                .filter_map(|s| match s {
-                    FlatStatement::Return(..) => None,
-                    s => Some(s.into()),
-                })
-                .chain(
-                    return_expressions
-                        .into_iter()
-                        .enumerate()
-                        .map(|(index, expression)| {
-                            Statement::Constraint(
-                                QuadComb::from_flat_expression(expression),
-                                FlatVariable::public(index).into(),
-                            )
-                        }),
-                )
-                .collect(),
+            statements,
+            debug: tracker.debug_info(flat_function.names),
         }
     }
 }
@@ -121,7 +130,7 @@ impl<T: Field> From<FlatStatement<T>> for Statement<T> {
                 e => Statement::Constraint(LinComb::from(e).into(), var.into()),
             },
             FlatStatement::Directive(ds) => Statement::Directive(ds.into()),
-            _ => panic!("return should be handled at the function level"),
+            _ => panic!("return and debug markers should be handled at the function level"),
         }
     }
 }
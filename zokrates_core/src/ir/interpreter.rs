@@ -1,14 +1,59 @@
 use crate::flat_absy::flat_variable::FlatVariable;
 use crate::helpers::Executable;
-use crate::ir::{LinComb, Prog, QuadComb, Statement, Witness};
+use crate::ir::{Directive, LinComb, Location, Prog, QuadComb, Statement, Witness};
 use std::collections::BTreeMap;
 use std::fmt;
 use zokrates_field::field::Field;
 
 pub type ExecutionResult<T> = Result<Witness<T>, Error>;
 
+/// What happened when evaluating a statement
+pub enum Event<'a, T: Field> {
+    /// A variable was assigned the value of a constraint
+    Assignment {
+        variable: &'a FlatVariable,
+        value: &'a T,
+    },
+    /// Both sides of a constraint were evaluated and compared
+    Check { left: &'a T, right: &'a T },
+    /// A directive was run, and either returned its outputs or failed
+    Directive {
+        directive: &'a Directive<T>,
+        inputs: &'a [T],
+        outputs: Result<&'a [T], &'a str>,
+    },
+}
+
+/// A hook into the execution of a program, notified of every statement as it is evaluated
+pub trait Observer<T: Field> {
+    /// Called after evaluating the statement at index `statement` in `main`, which comes from
+    /// `location` in the source code
+    fn observe(&mut self, statement: usize, location: Option<&Location>, event: Event<T>);
+
+    /// Whether execution goes on after a constraint is not satisfied, in which case it still
+    /// returns the first such error once all statements are evaluated
+    fn keep_going(&self) -> bool {
+        false
+    }
+}
+
+struct Silent;
+
+impl<T: Field> Observer<T> for Silent {
+    fn observe(&mut self, _: usize, _: Option<&Location>, _: Event<T>) {}
+}
+
 impl<T: Field> Prog<T> {
     pub fn execute(&self, inputs: &Vec<T>) -> ExecutionResult<T> {
+        self.execute_with(inputs, &mut Silent)
+    }
+
+    /// Executes the program, notifying `observer` of every statement evaluated
+    pub fn execute_with<O: Observer<T>>(
+        &self,
+        inputs: &Vec<T>,
+        observer: &mut O,
+    ) -> ExecutionResult<T> {
         let main = &self.main;
         self.check_inputs(&inputs)?;
         let mut witness = BTreeMap::new();
@@ -17,21 +62,50 @@ impl<T: Field> Prog<T> {
             witness.insert(arg.clone(), value.clone().into());
         }
 
-        for statement in &main.statements {
+        // the first unsatisfied constraint, if the observer keeps going after it
+        let mut failure = None;
+
+        for (index, statement) in main.statements.iter().enumerate() {
+            let location = self.location(index);
+
             match statement {
                 Statement::Constraint(quad, lin) => match lin.is_assignee(&witness) {
                     true => {
                         let val = quad.evaluate(&witness).unwrap();
-                        witness.insert(lin.0.iter().next().unwrap().0.clone(), val);
+                        let variable = lin.0.iter().next().unwrap().0.clone();
+                        observer.observe(
+                            index,
+                            location,
+                            Event::Assignment {
+                                variable: &variable,
+                                value: &val,
+                            },
+                        );
+                        witness.insert(variable, val);
                     }
                     false => {
                         let lhs_value = quad.evaluate(&witness).unwrap();
                         let rhs_value = lin.evaluate(&witness).unwrap();
+                        observer.observe(
+                            index,
+                            location,
+                            Event::Check {
+                                left: &lhs_value,
+                                right: &rhs_value,
+                            },
+                        );
                         if lhs_value != rhs_value {
-                            return Err(Error::UnsatisfiedConstraint {
+                            let error = Error::UnsatisfiedConstraint {
                                 left: lhs_value.to_dec_string(),
                                 right: rhs_value.to_dec_string(),
-                            });
+                                location: location.cloned(),
+                            };
+                            match observer.keep_going() {
+                                true => {
+                                    failure.get_or_insert(error);
+                                }
+                                false => return Err(error),
+                            }
                         }
                     }
                 },
@@ -41,20 +115,82 @@ impl<T: Field> Prog<T> {
                         .iter()
                         .map(|i| i.evaluate(&witness).unwrap())
                         .collect();
-                    match d.helper.execute(&input_values) {
+                    let result = d.helper.execute(&input_values);
+                    observer.observe(
+                        index,
+                        location,
+                        Event::Directive {
+                            directive: d,
+                            inputs: &input_values,
+                            outputs: match result {
+                                Ok(ref res) => Ok(&res[..]),
+                                Err(ref message) => Err(&message[..]),
+                            },
+                        },
+                    );
+                    match result {
                         Ok(res) => {
                             for (i, o) in d.outputs.iter().enumerate() {
                                 witness.insert(o.clone(), res[i].clone());
                             }
                             continue;
                         }
-                        Err(_) => return Err(Error::Solver),
+                        Err(message) => {
+                            return Err(Error::Solver {
+                                helper: d.helper.to_string(),
+                                message,
+                                location: location.cloned(),
+                            })
+                        }
                     };
                 }
             }
         }
 
-        Ok(Witness(witness))
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(Witness(witness)),
+        }
+    }
+
+    /// Checks that `witness` assigns every variable of the program and satisfies all its constraints
+    pub fn check_witness(&self, witness: &Witness<T>) -> Result<(), Error> {
+        if self
+            .main
+            .arguments
+            .iter()
+            .any(|a| !witness.0.contains_key(a))
+        {
+            return Err(Error::IncompleteWitness);
+        }
+
+        for (index, statement) in self.main.statements.iter().enumerate() {
+            if let Statement::Constraint(quad, lin) = statement {
+                let lhs_value = quad
+                    .evaluate(&witness.0)
+                    .map_err(|_| Error::IncompleteWitness)?;
+                let rhs_value = lin
+                    .evaluate(&witness.0)
+                    .map_err(|_| Error::IncompleteWitness)?;
+                if lhs_value != rhs_value {
+                    return Err(Error::UnsatisfiedConstraint {
+                        left: lhs_value.to_dec_string(),
+                        right: rhs_value.to_dec_string(),
+                        location: self.location(index).cloned(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The location in the source code of the statement of `main` at `index`, if known
+    fn location(&self, index: usize) -> Option<&Location> {
+        self.main
+            .debug
+            .as_ref()
+            .and_then(|debug| debug.location(index))
     }
 
     fn check_inputs<U>(&self, inputs: &Vec<U>) -> Result<(), Error> {
@@ -95,9 +231,23 @@ impl<T: Field> QuadComb<T> {
 
 #[derive(PartialEq, Serialize, Deserialize)]
 pub enum Error {
-    UnsatisfiedConstraint { left: String, right: String },
-    Solver,
-    WrongInputCount { expected: usize, received: usize },
+    UnsatisfiedConstraint {
+        left: String,
+        right: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<Location>,
+    },
+    Solver {
+        helper: String,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<Location>,
+    },
+    WrongInputCount {
+        expected: usize,
+        received: usize,
+    },
+    IncompleteWitness,
 }
 
 impl fmt::Display for Error {
@@ -106,8 +256,19 @@ impl fmt::Display for Error {
             Error::UnsatisfiedConstraint {
                 ref left,
                 ref right,
-            } => write!(f, "Expected {} to equal {}", left, right),
-            Error::Solver => write!(f, ""),
+                ref location,
+            } => {
+                write!(f, "Expected {} to equal {}", left, right)?;
+                write_location(f, location)
+            }
+            Error::Solver {
+                ref helper,
+                ref message,
+                ref location,
+            } => {
+                write!(f, "Helper {} failed: {}", helper, message)?;
+                write_location(f, location)
+            }
             Error::WrongInputCount { expected, received } => write!(
                 f,
                 "Program takes {} input{} but was passed {} value{}",
@@ -116,10 +277,20 @@ impl fmt::Display for Error {
                 received,
                 if received == 1 { "" } else { "s" }
             ),
+            Error::IncompleteWitness => {
+                write!(f, "Witness does not assign all variables of the program")
+            }
         }
     }
 }
 
+fn write_location(f: &mut fmt::Formatter, location: &Option<Location>) -> fmt::Result {
+    match location {
+        Some(location) => write!(f, " {}", location.describe()),
+        None => Ok(()),
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self)
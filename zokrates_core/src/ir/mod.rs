@@ -1,22 +1,41 @@
 use crate::flat_absy::flat_parameter::FlatParameter;
 use crate::flat_absy::FlatVariable;
 use crate::helpers::Helper;
+use bincode::{deserialize_from, serialize_into, Infinite};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::io::{self, Read, Write};
 use typed_absy::types::signature::Signature;
 use zokrates_field::field::Field;
 
+pub mod debug;
 mod expression;
 pub mod folder;
 mod from_flat;
 mod interpreter;
+pub mod profile;
+pub mod r1cs;
+pub mod trace;
 mod witness;
 
+pub use self::debug::{Call, DebugInfo, Location};
 pub use self::expression::QuadComb;
 pub use self::expression::{CanonicalLinComb, LinComb};
+pub use self::profile::Profile;
+pub use self::r1cs::R1cs;
+pub use self::trace::Trace;
 
-pub use self::interpreter::{Error, ExecutionResult};
+pub use self::interpreter::{Error, Event, ExecutionResult, Observer};
 pub use self::witness::Witness;
 
+const PROG_MAGIC: &[u8; 4] = b"zok\0";
+/// The version of the layout of compiled programs, to be bumped whenever it changes
+const PROG_VERSION: u32 = 1;
+const RECOMPILE: &str =
+    "not a compiled program, or compiled by another version of ZoKrates: please recompile it";
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Hash, Eq)]
 pub enum Statement<T: Field> {
     Constraint(QuadComb<T>, LinComb<T>),
@@ -75,23 +94,41 @@ pub struct Function<T: Field> {
     pub statements: Vec<Statement<T>>,
     pub arguments: Vec<FlatVariable>,
     pub returns: Vec<FlatVariable>,
+    /// Where the statements come from in the source code, if known
+    pub debug: Option<DebugInfo>,
 }
 
-impl<T: Field> fmt::Display for Function<T> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl<T: Field> Function<T> {
+    /// Writes the function, with the variables named after the source code where known if `named`
+    /// is set, and each statement followed by its location in the source code if `located` is set
+    fn fmt_annotated(&self, f: &mut fmt::Formatter, named: bool, located: bool) -> fmt::Result {
+        let rename = |s: String| match (named, &self.debug) {
+            (true, Some(debug)) => debug.rename(&s),
+            _ => s,
+        };
+
         write!(
             f,
             "def {}({}) -> ({}):\n{}\n\t return {}",
             self.id,
-            self.arguments
-                .iter()
-                .map(|v| format!("{}", v))
-                .collect::<Vec<_>>()
-                .join(", "),
+            rename(
+                self.arguments
+                    .iter()
+                    .map(|v| format!("{}", v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             self.returns.len(),
             self.statements
                 .iter()
-                .map(|s| format!("\t{}", s))
+                .enumerate()
+                .map(|(i, s)| {
+                    let s = rename(s.to_string());
+                    match self.debug.as_ref().and_then(|d| d.location(i)) {
+                        Some(location) if located => format!("\t{} // {}", s, location),
+                        _ => format!("\t{}", s),
+                    }
+                })
                 .collect::<Vec<_>>()
                 .join("\n"),
             self.returns
@@ -103,6 +140,12 @@ impl<T: Field> fmt::Display for Function<T> {
     }
 }
 
+impl<T: Field> fmt::Display for Function<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_annotated(f, false, false)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Prog<T: Field> {
     pub signature: Signature,
@@ -139,12 +182,82 @@ impl<T: Field> Prog<T> {
     }
 }
 
+impl<T: Field + Serialize + DeserializeOwned> Prog<T> {
+    /// Writes the program as a compiled `out` file: a magic string and the version of the layout,
+    /// followed by the program encoded with bincode
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(PROG_MAGIC)?;
+        writer.write_all(&PROG_VERSION.to_le_bytes())?;
+        serialize_into(&mut writer, self, Infinite)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        writer.flush()
+    }
+
+    /// Reads a program written by `write`. Programs compiled by a version of ZoKrates with
+    /// another layout are rejected, as they cannot be decoded reliably
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0u8; 4];
+        let mut version = [0u8; 4];
+        reader
+            .read_exact(&mut magic)
+            .and_then(|_| reader.read_exact(&mut version))
+            .map_err(|_| invalid(RECOMPILE))?;
+        if &magic != PROG_MAGIC || u32::from_le_bytes(version) != PROG_VERSION {
+            return Err(invalid(RECOMPILE));
+        }
+
+        deserialize_from(&mut reader, Infinite).map_err(|e| invalid(&e.to_string()))
+    }
+}
+
 impl<T: Field> fmt::Display for Prog<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.main)
     }
 }
 
+/// A program displayed with its variables named after the source code and its statements
+/// followed by their location, where known and requested
+pub struct Annotated<'a, T: Field> {
+    prog: &'a Prog<T>,
+    names: bool,
+    locations: bool,
+}
+
+impl<T: Field> Prog<T> {
+    /// Displays the program with the names of the variables instead of `_42` where known
+    pub fn named(&self) -> Annotated<T> {
+        self.annotated(true, false)
+    }
+
+    /// Displays the program with the names of the variables instead of `_42` if `names` is set,
+    /// and each statement followed by its location in the source code if `locations` is set
+    pub fn annotated(&self, names: bool, locations: bool) -> Annotated<T> {
+        Annotated {
+            prog: self,
+            names,
+            locations,
+        }
+    }
+
+    /// The names of the variables of the program in the source code
+    pub fn names(&self) -> BTreeMap<FlatVariable, String> {
+        self.main
+            .debug
+            .as_ref()
+            .map(|d| d.names.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl<'a, T: Field> fmt::Display for Annotated<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.prog.main.fmt_annotated(f, self.names, self.locations)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,4 +278,95 @@ mod tests {
             assert_eq!(format!("{}", c), "(1 * _42) * (1 * _42) == 1 * _42")
         }
     }
+
+    mod prog {
+        use super::*;
+        use crate::parser::Position;
+        use crate::typed_absy::SourceSpan;
+
+        #[test]
+        fn print_named() {
+            let prog: Prog<FieldPrime> = Prog {
+                main: Function {
+                    id: String::from("main"),
+                    statements: vec![Statement::definition(
+                        FlatVariable::new(1),
+                        QuadComb::from_linear_combinations(
+                            FlatVariable::new(0).into(),
+                            FlatVariable::new(0).into(),
+                        ),
+                    )],
+                    arguments: vec![FlatVariable::new(0)],
+                    returns: vec![],
+                    debug: Some(DebugInfo {
+                        locations: vec![Location {
+                            span: SourceSpan {
+                                module: String::from("./main.zok"),
+                                start: Position { line: 2, col: 5 },
+                                end: Position { line: 2, col: 19 },
+                                text: String::from("field b = a * a"),
+                            },
+                            function: String::from("main"),
+                            call_stack: vec![],
+                        }],
+                        statements: vec![Some(0)],
+                        names: vec![(FlatVariable::new(0), String::from("a"))]
+                            .into_iter()
+                            .collect(),
+                    }),
+                },
+                private: vec![true],
+                signature: Signature::new(),
+            };
+
+            assert_eq!(
+                prog.named().to_string(),
+                "def main(a) -> (0):\n\t(1 * a) * (1 * a) == 1 * _1\n\t return "
+            );
+            assert_eq!(
+                prog.to_string(),
+                "def main(_0) -> (0):\n\t(1 * _0) * (1 * _0) == 1 * _1\n\t return "
+            );
+
+            // locations are only printed when requested
+            assert_eq!(
+                prog.annotated(true, true).to_string(),
+                "def main(a) -> (0):\n\t(1 * a) * (1 * a) == 1 * _1 // ./main.zok:2:5\n\t return "
+            );
+        }
+
+        #[test]
+        fn read_write() {
+            let prog: Prog<FieldPrime> = Prog {
+                main: Function {
+                    id: String::from("main"),
+                    statements: vec![Statement::constraint(
+                        FlatVariable::new(0),
+                        FlatVariable::new(0),
+                    )],
+                    arguments: vec![FlatVariable::new(0)],
+                    returns: vec![],
+                    debug: None,
+                },
+                private: vec![true],
+                signature: Signature::new(),
+            };
+
+            let mut buffer = vec![];
+            prog.write(&mut buffer).unwrap();
+            assert_eq!(Prog::<FieldPrime>::read(&buffer[..]).unwrap(), prog);
+
+            // a program written without the header, or with another layout, must be recompiled
+            let mut bare = vec![];
+            serialize_into(&mut bare, &prog, Infinite).unwrap();
+            let mut other_version = buffer.clone();
+            other_version[4] += 1;
+
+            for bytes in &[bare, other_version] {
+                let e = Prog::<FieldPrime>::read(&bytes[..]).unwrap_err();
+                assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                assert!(e.to_string().contains("please recompile it"));
+            }
+        }
+    }
 }
@@ -0,0 +1,286 @@
+//! Attribution of the constraints of a program to the source code they were compiled from
+
+use crate::ir::{Location, Prog, Statement};
+use std::collections::HashMap;
+use std::fmt;
+use zokrates_field::field::Field;
+
+/// The number of constraints attributed to a module, function, line or call stack
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub constraints: usize,
+}
+
+/// A breakdown of the constraints of a program, each list being sorted by decreasing number of
+/// constraints
+#[derive(Debug, PartialEq)]
+pub struct Profile {
+    /// The total number of constraints
+    pub constraints: usize,
+    /// The number of constraints which cannot be traced back to the source code
+    pub unknown: usize,
+    /// The constraints per module
+    pub modules: Vec<Entry>,
+    /// The constraints per function, named as `function (module)`
+    pub functions: Vec<Entry>,
+    /// The constraints per source line, named as `module:line: statement`
+    pub lines: Vec<Entry>,
+    /// The constraints per call stack, named as the `;`-separated list of the functions called,
+    /// followed by the source line
+    pub stacks: Vec<Entry>,
+}
+
+const UNKNOWN: &str = "[unknown]";
+
+impl<T: Field> Prog<T> {
+    /// Attributes every constraint of the program to the function and source line it comes from
+    pub fn profile(&self) -> Profile {
+        let mut counts: HashMap<Option<&Location>, usize> = HashMap::new();
+
+        for (index, statement) in self.main.statements.iter().enumerate() {
+            if let Statement::Constraint(..) = statement {
+                let location = self.main.debug.as_ref().and_then(|d| d.location(index));
+                *counts.entry(location).or_insert(0) += 1;
+            }
+        }
+
+        let mut modules = HashMap::new();
+        let mut functions = HashMap::new();
+        let mut lines = HashMap::new();
+        let mut stacks = HashMap::new();
+
+        for (location, count) in &counts {
+            match location {
+                Some(location) => {
+                    let span = &location.span;
+                    *modules.entry(span.module.clone()).or_insert(0) += count;
+                    *functions
+                        .entry(format!("{} ({})", location.function, span.module))
+                        .or_insert(0) += count;
+                    // statements spanning the same line are counted together, and shown as the
+                    // leftmost one so that the output does not depend on the iteration order
+                    let line = lines
+                        .entry(format!("{}:{}", span.module, span.start.line))
+                        .or_insert((0, span));
+                    line.0 += count;
+                    if (span.start.col, &span.text) < (line.1.start.col, &line.1.text) {
+                        line.1 = span;
+                    }
+                    *stacks.entry(stack(location)).or_insert(0) += count;
+                }
+                None => *stacks.entry(UNKNOWN.to_string()).or_insert(0) += count,
+            }
+        }
+
+        Profile {
+            constraints: self.constraint_count(),
+            unknown: *counts.get(&None).unwrap_or(&0),
+            modules: sorted(modules),
+            functions: sorted(functions),
+            lines: sorted(
+                lines
+                    .into_iter()
+                    .map(|(line, (count, span))| (format!("{}: {}", line, span.text), count))
+                    .collect(),
+            ),
+            stacks: sorted(stacks),
+        }
+    }
+}
+
+/// The frames of the call stack of `location`, outermost first, in the folded stack format
+fn stack(location: &Location) -> String {
+    location
+        .call_stack
+        .iter()
+        .map(|call| call.function.clone())
+        .chain(vec![
+            location.function.clone(),
+            format!("{}:{}", location.span.module, location.span.start.line),
+        ])
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn sorted(counts: HashMap<String, usize>) -> Vec<Entry> {
+    let mut entries: Vec<_> = counts
+        .into_iter()
+        .map(|(name, constraints)| Entry { name, constraints })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.constraints
+            .cmp(&a.constraints)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
+
+impl Profile {
+    /// The call stacks in the folded format read by flamegraph tools, one `stack count` per line
+    pub fn folded(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|e| format!("{} {}\n", e.name, e.constraints))
+            .collect()
+    }
+
+    fn fmt_entries(&self, f: &mut fmt::Formatter, title: &str, entries: &[Entry]) -> fmt::Result {
+        writeln!(f, "\n{}:", title)?;
+        for e in entries {
+            writeln!(
+                f,
+                "{:>10} {:>6.2}%  {}",
+                e.constraints,
+                100.0 * e.constraints as f64 / self.constraints as f64,
+                e.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Number of constraints: {}", self.constraints)?;
+        self.fmt_entries(f, "By module", &self.modules)?;
+        self.fmt_entries(f, "By function", &self.functions)?;
+        self.fmt_entries(f, "By line", &self.lines)?;
+        if self.unknown > 0 {
+            writeln!(
+                f,
+                "\n{} constraints could not be traced back to the source code",
+                self.unknown
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Call, DebugInfo, Function, LinComb};
+    use crate::parser::Position;
+    use crate::typed_absy::types::Signature;
+    use crate::typed_absy::SourceSpan;
+    use std::collections::BTreeMap;
+    use zokrates_field::field::FieldPrime;
+
+    fn span(module: &str, line: usize, text: &str) -> SourceSpan {
+        SourceSpan {
+            module: String::from(module),
+            start: Position { line, col: 4 },
+            end: Position { line, col: 10 },
+            text: String::from(text),
+        }
+    }
+
+    #[test]
+    fn profile() {
+        let constraint = Statement::constraint(LinComb::one(), LinComb::one());
+
+        // two constraints in `main`, one in `foo` called from `main`, and one of unknown origin
+        let prog: Prog<FieldPrime> = Prog {
+            main: Function {
+                id: String::from("main"),
+                statements: vec![
+                    constraint.clone(),
+                    constraint.clone(),
+                    constraint.clone(),
+                    constraint.clone(),
+                ],
+                arguments: vec![],
+                returns: vec![],
+                debug: Some(DebugInfo {
+                    locations: vec![
+                        Location {
+                            span: span(".", 2, "a == 1"),
+                            function: String::from("main"),
+                            call_stack: vec![],
+                        },
+                        Location {
+                            span: span("./lib", 5, "b == 1"),
+                            function: String::from("foo"),
+                            call_stack: vec![Call {
+                                span: span(".", 3, "field b = foo(a)"),
+                                function: String::from("main"),
+                            }],
+                        },
+                    ],
+                    statements: vec![Some(0), Some(1), None, Some(0)],
+                    names: BTreeMap::new(),
+                }),
+            },
+            private: vec![],
+            signature: Signature::new(),
+        };
+
+        let profile = prog.profile();
+
+        let entries = |entries: Vec<(&str, usize)>| {
+            entries
+                .into_iter()
+                .map(|(name, constraints)| Entry {
+                    name: String::from(name),
+                    constraints,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(profile.constraints, 4);
+        assert_eq!(profile.unknown, 1);
+        assert_eq!(profile.modules, entries(vec![(".", 2), ("./lib", 1)]));
+        assert_eq!(
+            profile.functions,
+            entries(vec![("main (.)", 2), ("foo (./lib)", 1)])
+        );
+        assert_eq!(
+            profile.lines,
+            entries(vec![(".:2: a == 1", 2), ("./lib:5: b == 1", 1)])
+        );
+        assert_eq!(
+            profile.folded(),
+            "main;.:2 2\n[unknown] 1\nmain;foo;./lib:5 1\n"
+        );
+    }
+
+    #[test]
+    fn statements_on_the_same_line() {
+        let constraint = Statement::constraint(LinComb::one(), LinComb::one());
+
+        let location = |col: usize, text: &str| Location {
+            span: SourceSpan {
+                start: Position { line: 2, col },
+                ..span(".", 2, text)
+            },
+            function: String::from("main"),
+            call_stack: vec![],
+        };
+
+        let prog: Prog<FieldPrime> = Prog {
+            main: Function {
+                id: String::from("main"),
+                statements: vec![constraint.clone(), constraint.clone(), constraint],
+                arguments: vec![],
+                returns: vec![],
+                debug: Some(DebugInfo {
+                    locations: vec![location(12, "b == 1"), location(4, "a == 1")],
+                    statements: vec![Some(0), Some(0), Some(1)],
+                    names: BTreeMap::new(),
+                }),
+            },
+            private: vec![],
+            signature: Signature::new(),
+        };
+
+        // the leftmost statement is shown
+        assert_eq!(
+            prog.profile().lines,
+            vec![Entry {
+                name: String::from(".:2: a == 1"),
+                constraints: 3,
+            }]
+        );
+    }
+}
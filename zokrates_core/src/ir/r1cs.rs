@@ -0,0 +1,849 @@
+//! Export of the constraints of a program as a rank-1 constraint system, in the binary `.r1cs`
+//! format defined by iden3 and read by circom and snarkjs, or in the matching JSON format, and of
+//! witnesses in the matching binary `.wtns` format. Constraint systems and witnesses in these
+//! formats can be imported as well, to use circuits built with other tools
+
+use crate::flat_absy::FlatVariable;
+use crate::ir::{Function, LinComb, Prog, QuadComb, Statement, Witness};
+use crate::typed_absy::types::{Signature, Type};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::io::{Read, Write};
+use zokrates_field::field::Field;
+
+const MAGIC: &[u8; 4] = b"r1cs";
+const VERSION: u32 = 1;
+
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const WTNS_VERSION: u32 = 2;
+
+const WTNS_HEADER_SECTION: u32 = 1;
+const WTNS_VALUES_SECTION: u32 = 2;
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE_TO_LABEL_SECTION: u32 = 3;
+
+/// A linear combination of wires, sorted by wire
+pub type Combination<T> = Vec<(usize, T)>;
+
+/// The constraints of a program over numbered wires, each constraint `(a, b, c)` enforcing
+/// `a * b == c`
+#[derive(Debug, PartialEq)]
+pub struct R1cs<T: Field> {
+    /// The variable of each wire: `~one`, then the outputs, the public inputs, the private inputs
+    /// and the other variables in the order they appear in the constraints
+    pub wires: Vec<FlatVariable>,
+    pub outputs: usize,
+    pub public_inputs: usize,
+    pub private_inputs: usize,
+    pub constraints: Vec<(Combination<T>, Combination<T>, Combination<T>)>,
+}
+
+impl<T: Field> Prog<T> {
+    /// Numbers the variables of the program and lists its constraints over them
+    pub fn r1cs(&self) -> R1cs<T> {
+        let mut indices = HashMap::new();
+        let mut wires = vec![];
+
+        let mut wire = |variable: FlatVariable| {
+            *indices.entry(variable).or_insert_with(|| {
+                wires.push(variable);
+                wires.len() - 1
+            })
+        };
+
+        wire(FlatVariable::one());
+
+        let outputs = self.main.returns.len();
+        for i in 0..outputs {
+            wire(FlatVariable::public(i));
+        }
+
+        let inputs = self.main.arguments.iter().zip(self.private.iter());
+
+        let public_inputs = inputs.clone().filter(|(_, private)| !**private).count();
+        for (argument, _) in inputs.clone().filter(|(_, private)| !**private) {
+            wire(*argument);
+        }
+
+        let private_inputs = inputs.clone().filter(|(_, private)| **private).count();
+        for (argument, _) in inputs.filter(|(_, private)| **private) {
+            wire(*argument);
+        }
+
+        let mut combination = |lin: &LinComb<T>| {
+            let mut res: Vec<_> = lin
+                .as_canonical()
+                .0
+                .into_iter()
+                .map(|(variable, coefficient)| (wire(variable), coefficient))
+                .collect();
+            res.sort_by_key(|(wire, _)| *wire);
+            res
+        };
+
+        let constraints = self
+            .main
+            .statements
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Constraint(quad, lin) => Some((
+                    combination(&quad.left),
+                    combination(&quad.right),
+                    combination(lin),
+                )),
+                Statement::Directive(..) => None,
+            })
+            .collect();
+
+        R1cs {
+            wires,
+            outputs,
+            public_inputs,
+            private_inputs,
+            constraints,
+        }
+    }
+}
+
+impl<T: Field> From<R1cs<T>> for Prog<T> {
+    /// Builds a program enforcing the constraints, with the public and private inputs of the
+    /// constraint system as arguments. As it has no directives, it cannot compute its witness
+    fn from(r1cs: R1cs<T>) -> Prog<T> {
+        let wires = &r1cs.wires;
+        let combination = |c: &Combination<T>| {
+            LinComb(
+                c.iter()
+                    .map(|(wire, coefficient)| (wires[*wire], coefficient.clone()))
+                    .collect(),
+            )
+        };
+
+        let statements = r1cs
+            .constraints
+            .iter()
+            .map(|(a, b, c)| {
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(combination(a), combination(b)),
+                    combination(c),
+                )
+            })
+            .collect();
+
+        let inputs = r1cs.public_inputs + r1cs.private_inputs;
+        let arguments = wires[1 + r1cs.outputs..1 + r1cs.outputs + inputs].to_vec();
+
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                statements,
+                arguments,
+                returns: wires[1..1 + r1cs.outputs].to_vec(),
+                debug: None,
+            },
+            private: (0..inputs).map(|i| i >= r1cs.public_inputs).collect(),
+            signature: Signature::new()
+                .inputs(vec![Type::FieldElement; inputs])
+                .outputs(vec![Type::FieldElement; r1cs.outputs]),
+        }
+    }
+}
+
+/// The JSON representation of a constraint system, as written by `snarkjs r1cs export json`, with
+/// the variable of each wire
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Json {
+    n8: usize,
+    prime: String,
+    n_vars: usize,
+    n_outputs: usize,
+    n_pub_inputs: usize,
+    n_prv_inputs: usize,
+    n_labels: u64,
+    n_constraints: usize,
+    constraints: Vec<Vec<BTreeMap<String, String>>>,
+    map: Vec<u64>,
+    variables: Vec<String>,
+}
+
+impl<T: Field> R1cs<T> {
+    /// The label of the variable of each wire. Labels number `~one` first, then the outputs, and
+    /// then the other variables by index, so that `_n` has label `1 + outputs + n`
+    pub fn labels(&self) -> Vec<u64> {
+        self.wires
+            .iter()
+            .enumerate()
+            .map(|(wire, variable)| match variable {
+                // the outputs are wired in order right after `~one`
+                v if v.is_output() => wire as u64,
+                v if *v == FlatVariable::one() => 0,
+                v => (1 + self.outputs + v.id()) as u64,
+            })
+            .collect()
+    }
+
+    fn label_count(&self) -> u64 {
+        self.labels().into_iter().max().map(|l| l + 1).unwrap_or(0)
+    }
+
+    /// Reads a constraint system in the binary `.r1cs` format, version 1. Wire `0` is `~one`, the
+    /// next wires are the outputs `~out_i`, and every other wire `w` is the variable
+    /// `_(w - 1 - outputs)`, so that each wire keeps its number and its label in an export
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid(String::from("not a .r1cs file")));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            return Err(invalid(format!("unsupported .r1cs version {}", version)));
+        }
+
+        let mut header = None;
+        let mut constraints = None;
+
+        for _ in 0..read_u32(&mut reader)? {
+            let (section, content) = read_section(&mut reader)?;
+
+            match section {
+                HEADER_SECTION => header = Some(content),
+                CONSTRAINTS_SECTION => constraints = Some(content),
+                // the labels are not needed to number the wires
+                _ => {}
+            }
+        }
+
+        let header = header.ok_or_else(|| invalid(String::from("missing header section")))?;
+        let mut header = &header[..];
+
+        let n8 = field_size::<T>();
+        if read_u32(&mut header)? as usize != n8 {
+            return Err(invalid(String::from("unexpected field element size")));
+        }
+        let mut prime_bytes = vec![0u8; n8];
+        header.read_exact(&mut prime_bytes)?;
+        let prime = prime::<T>();
+        if BigUint::from_bytes_le(&prime_bytes) != prime {
+            return Err(invalid(String::from("unexpected field")));
+        }
+
+        let wire_count = read_u32(&mut header)? as usize;
+        let outputs = read_u32(&mut header)? as usize;
+        let public_inputs = read_u32(&mut header)? as usize;
+        let private_inputs = read_u32(&mut header)? as usize;
+        let _labels = read_u64(&mut header)?;
+        let constraint_count = read_u32(&mut header)?;
+
+        if 1 + outputs + public_inputs + private_inputs > wire_count {
+            return Err(invalid(String::from("more inputs and outputs than wires")));
+        }
+
+        let constraints =
+            constraints.ok_or_else(|| invalid(String::from("missing constraints section")))?;
+        let mut constraints = &constraints[..];
+
+        let mut combination = || -> io::Result<Combination<T>> {
+            (0..read_u32(&mut constraints)?)
+                .map(|_| -> io::Result<(usize, T)> {
+                    let wire = read_u32(&mut constraints)? as usize;
+                    if wire >= wire_count {
+                        return Err(invalid(format!("unknown wire {}", wire)));
+                    }
+                    Ok((wire, read_element(&mut constraints, n8, &prime)?))
+                })
+                .collect()
+        };
+
+        let constraints = (0..constraint_count)
+            .map(|_| -> io::Result<_> { Ok((combination()?, combination()?, combination()?)) })
+            .collect::<io::Result<_>>()?;
+
+        Ok(R1cs {
+            wires: (0..wire_count)
+                .map(|wire| match wire {
+                    0 => FlatVariable::one(),
+                    w if w <= outputs => FlatVariable::public(w - 1),
+                    w => FlatVariable::new(w - 1 - outputs),
+                })
+                .collect(),
+            outputs,
+            public_inputs,
+            private_inputs,
+            constraints,
+        })
+    }
+
+    /// Writes the constraint system in the binary `.r1cs` format, version 1
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let n8 = field_size::<T>();
+
+        let mut header = vec![];
+        header.extend(&(n8 as u32).to_le_bytes());
+        header.extend(to_bytes(prime::<T>(), n8));
+        header.extend(&(self.wires.len() as u32).to_le_bytes());
+        header.extend(&(self.outputs as u32).to_le_bytes());
+        header.extend(&(self.public_inputs as u32).to_le_bytes());
+        header.extend(&(self.private_inputs as u32).to_le_bytes());
+        header.extend(&self.label_count().to_le_bytes());
+        header.extend(&(self.constraints.len() as u32).to_le_bytes());
+
+        let mut constraints = vec![];
+        for (a, b, c) in &self.constraints {
+            for combination in &[a, b, c] {
+                constraints.extend(&(combination.len() as u32).to_le_bytes());
+                for (wire, coefficient) in combination.iter() {
+                    constraints.extend(&(*wire as u32).to_le_bytes());
+                    constraints.extend(to_bytes(
+                        BigUint::from_bytes_le(&coefficient.into_byte_vector()),
+                        n8,
+                    ));
+                }
+            }
+        }
+
+        let mut map = vec![];
+        for label in self.labels() {
+            map.extend(&label.to_le_bytes());
+        }
+
+        write_sections(
+            &mut writer,
+            MAGIC,
+            VERSION,
+            &[
+                (HEADER_SECTION, header),
+                (CONSTRAINTS_SECTION, constraints),
+                (WIRE_TO_LABEL_SECTION, map),
+            ],
+        )
+    }
+
+    /// Writes the constraint system in JSON, with wires as keys and coefficients as decimal strings
+    pub fn write_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        let combination = |c: &Combination<T>| {
+            c.iter()
+                .map(|(wire, coefficient)| (wire.to_string(), coefficient.to_dec_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let json = Json {
+            n8: field_size::<T>(),
+            prime: prime::<T>().to_str_radix(10),
+            n_vars: self.wires.len(),
+            n_outputs: self.outputs,
+            n_pub_inputs: self.public_inputs,
+            n_prv_inputs: self.private_inputs,
+            n_labels: self.label_count(),
+            n_constraints: self.constraints.len(),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|(a, b, c)| vec![combination(a), combination(b), combination(c)])
+                .collect(),
+            map: self.labels(),
+            variables: self.wires.iter().map(|v| v.to_string()).collect(),
+        };
+
+        serde_json::to_writer_pretty(writer, &json).map_err(io::Error::from)
+    }
+}
+
+impl<T: Field> Witness<T> {
+    /// The values of `wires` in order, as numbered by `Prog::r1cs`
+    fn wire_values(&self, wires: &[FlatVariable]) -> io::Result<Vec<&T>> {
+        wires
+            .iter()
+            .map(|variable| {
+                self.0.get(variable).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("no value for {} in the witness", variable),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the values of `wires` in the binary `.wtns` format, version 2
+    pub fn write_wtns<W: Write>(&self, wires: &[FlatVariable], mut writer: W) -> io::Result<()> {
+        let n8 = field_size::<T>();
+
+        let mut header = vec![];
+        header.extend(&(n8 as u32).to_le_bytes());
+        header.extend(to_bytes(prime::<T>(), n8));
+        header.extend(&(wires.len() as u32).to_le_bytes());
+
+        let mut values = vec![];
+        for value in self.wire_values(wires)? {
+            values.extend(to_bytes(
+                BigUint::from_bytes_le(&value.into_byte_vector()),
+                n8,
+            ));
+        }
+
+        write_sections(
+            &mut writer,
+            WTNS_MAGIC,
+            WTNS_VERSION,
+            &[(WTNS_HEADER_SECTION, header), (WTNS_VALUES_SECTION, values)],
+        )
+    }
+
+    /// Reads a witness written in the binary `.wtns` format, version 2, assigning the values to
+    /// `wires` in order
+    pub fn read_wtns<R: Read>(wires: &[FlatVariable], mut reader: R) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != WTNS_MAGIC {
+            return Err(invalid(String::from("not a .wtns file")));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != WTNS_VERSION {
+            return Err(invalid(format!("unsupported .wtns version {}", version)));
+        }
+
+        let mut header = None;
+        let mut values = None;
+
+        for _ in 0..read_u32(&mut reader)? {
+            let (section, content) = read_section(&mut reader)?;
+
+            match section {
+                WTNS_HEADER_SECTION => header = Some(content),
+                WTNS_VALUES_SECTION => values = Some(content),
+                // unknown sections are skipped
+                _ => {}
+            }
+        }
+
+        let header = header.ok_or_else(|| invalid(String::from("missing header section")))?;
+        let mut header = &header[..];
+
+        let n8 = field_size::<T>();
+        if read_u32(&mut header)? as usize != n8 {
+            return Err(invalid(String::from("unexpected field element size")));
+        }
+        let mut prime_bytes = vec![0u8; n8];
+        header.read_exact(&mut prime_bytes)?;
+        let prime = prime::<T>();
+        if BigUint::from_bytes_le(&prime_bytes) != prime {
+            return Err(invalid(String::from("unexpected field")));
+        }
+        let count = read_u32(&mut header)? as usize;
+        if count != wires.len() {
+            return Err(invalid(format!(
+                "expected {} values, found {}",
+                wires.len(),
+                count
+            )));
+        }
+
+        let values = values.ok_or_else(|| invalid(String::from("missing values section")))?;
+        if values.len() != wires.len() * n8 {
+            return Err(invalid(String::from(
+                "unexpected size of the values section",
+            )));
+        }
+
+        let mut values = &values[..];
+
+        Ok(Witness(
+            wires
+                .iter()
+                .map(|wire| Ok((*wire, read_element(&mut values, n8, &prime)?)))
+                .collect::<io::Result<_>>()?,
+        ))
+    }
+
+    /// Writes the values of `wires` in order as a JSON array of decimal strings, as written by
+    /// `snarkjs wtns export json`
+    pub fn write_wires_json<W: Write>(&self, wires: &[FlatVariable], writer: W) -> io::Result<()> {
+        let values: Vec<_> = self
+            .wire_values(wires)?
+            .into_iter()
+            .map(|v| v.to_dec_string())
+            .collect();
+
+        serde_json::to_writer_pretty(writer, &values).map_err(io::Error::from)
+    }
+}
+
+/// Writes a file in the format shared by `.r1cs` and `.wtns`: a magic string, a version and a list
+/// of sections, each preceded by its type and size
+fn write_sections<W: Write>(
+    writer: &mut W,
+    magic: &[u8; 4],
+    version: u32,
+    sections: &[(u32, Vec<u8>)],
+) -> io::Result<()> {
+    writer.write_all(magic)?;
+    writer.write_all(&version.to_le_bytes())?;
+    writer.write_all(&(sections.len() as u32).to_le_bytes())?;
+
+    for (section, content) in sections {
+        writer.write_all(&section.to_le_bytes())?;
+        writer.write_all(&(content.len() as u64).to_le_bytes())?;
+        writer.write_all(content)?;
+    }
+
+    writer.flush()
+}
+
+/// Reads the type and content of a section. The content is read as it comes rather than
+/// allocated upfront, as the size announced by a truncated or hostile file cannot be trusted
+fn read_section<R: Read>(reader: &mut R) -> io::Result<(u32, Vec<u8>)> {
+    let section = read_u32(reader)?;
+    let size = read_u64(reader)?;
+
+    let mut content = vec![];
+    reader.by_ref().take(size).read_to_end(&mut content)?;
+    if content.len() as u64 != size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("section {} is truncated", section),
+        ));
+    }
+
+    Ok((section, content))
+}
+
+/// Reads a field element written on `n8` bytes, which must be smaller than `prime`
+fn read_element<T: Field, R: Read>(reader: &mut R, n8: usize, prime: &BigUint) -> io::Result<T> {
+    let mut bytes = vec![0u8; n8];
+    reader.read_exact(&mut bytes)?;
+
+    if BigUint::from_bytes_le(&bytes) >= *prime {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "field element out of range",
+        ));
+    }
+
+    Ok(T::from_byte_vector(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// The modulus of the field
+fn prime<T: Field>() -> BigUint {
+    BigUint::from_bytes_le(&T::max_value().into_byte_vector()) + BigUint::from(1u64)
+}
+
+/// The number of bytes used to write an element of the field, a multiple of 8
+fn field_size<T: Field>() -> usize {
+    (T::get_required_bits() + 63) / 64 * 8
+}
+
+/// The little-endian representation of `value` on `size` bytes
+fn to_bytes(value: BigUint, size: usize) -> Vec<u8> {
+    let mut bytes = value.to_bytes_le();
+    bytes.resize(size, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{Helper, RustHelper};
+    use crate::ir::{Directive, Function, QuadComb};
+    use crate::typed_absy::types::Signature;
+    use serde_json::Value;
+    use zokrates_field::field::FieldPrime;
+
+    // def main(private field a, field b) -> (field):
+    //     field c = a * a
+    //     return c + b
+    fn prog() -> Prog<FieldPrime> {
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                statements: vec![
+                    Statement::definition(
+                        FlatVariable::new(2),
+                        QuadComb::from_linear_combinations(
+                            FlatVariable::new(0).into(),
+                            FlatVariable::new(0).into(),
+                        ),
+                    ),
+                    Statement::Directive(Directive {
+                        inputs: vec![FlatVariable::new(2).into()],
+                        outputs: vec![FlatVariable::new(3)],
+                        helper: Helper::Rust(RustHelper::Identity),
+                    }),
+                    Statement::constraint(
+                        LinComb::from(FlatVariable::new(2)) + LinComb::from(FlatVariable::new(1)),
+                        FlatVariable::public(0),
+                    ),
+                ],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+                debug: None,
+            },
+            private: vec![true, false],
+            signature: Signature::new(),
+        }
+    }
+
+    #[test]
+    fn wires() {
+        let r1cs = prog().r1cs();
+
+        // `~one`, the outputs, the public inputs, the private inputs and the other variables
+        assert_eq!(
+            r1cs.wires,
+            vec![
+                FlatVariable::one(),
+                FlatVariable::public(0),
+                FlatVariable::new(1),
+                FlatVariable::new(0),
+                FlatVariable::new(2),
+            ]
+        );
+        assert_eq!(
+            (r1cs.outputs, r1cs.public_inputs, r1cs.private_inputs),
+            (1, 1, 1)
+        );
+        assert_eq!(r1cs.labels(), vec![0, 1, 3, 2, 4]);
+
+        let one = || FieldPrime::from(1);
+        assert_eq!(
+            r1cs.constraints,
+            vec![
+                (vec![(3, one())], vec![(3, one())], vec![(4, one())]),
+                (
+                    vec![(0, one())],
+                    vec![(2, one()), (4, one())],
+                    vec![(1, one())]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn write() {
+        let mut buffer = vec![];
+        prog().r1cs().write(&mut buffer).unwrap();
+
+        // magic, version and number of sections
+        assert_eq!(&buffer[0..4], b"r1cs");
+        assert_eq!(&buffer[4..12], &[1, 0, 0, 0, 3, 0, 0, 0]);
+
+        // the header section starts with the size of field elements, followed by the prime
+        assert_eq!(&buffer[12..16], &[1, 0, 0, 0]);
+        assert_eq!(&buffer[16..24], &[64, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&buffer[24..28], &[32, 0, 0, 0]);
+        // followed by the number of wires
+        assert_eq!(&buffer[60..64], &[5, 0, 0, 0]);
+
+        // each section is preceded by its type and size
+        let header = 12 + 64;
+        let constraints = 12 + 3 * (4 + 36) + 2 * (4 + 36) + (4 + 2 * 36);
+        let map = 12 + 5 * 8;
+        assert_eq!(buffer.len(), 12 + header + constraints + map);
+    }
+
+    #[test]
+    fn write_json() {
+        let mut buffer = vec![];
+        prog().r1cs().write_json(&mut buffer).unwrap();
+
+        let json: Value = serde_json::from_slice(&buffer).unwrap();
+
+        assert_eq!(json["n8"], 32);
+        assert_eq!(
+            json["prime"],
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+        );
+        assert_eq!(json["nVars"], 5);
+        assert_eq!(json["nPubInputs"], 1);
+        assert_eq!(json["nLabels"], 5);
+        assert_eq!(json["constraints"][1][1]["4"], "1");
+        assert_eq!(json["map"][2], 3);
+        assert_eq!(json["variables"][3], "_0");
+    }
+
+    #[test]
+    fn wtns_round_trip() {
+        let prog = prog();
+        let wires = prog.r1cs().wires;
+        let witness = prog
+            .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let mut buffer = vec![];
+        witness.write_wtns(&wires, &mut buffer).unwrap();
+
+        assert_eq!(&buffer[0..4], b"wtns");
+        assert_eq!(buffer.len(), 12 + (12 + 4 + 32 + 4) + (12 + 5 * 32));
+
+        let read = Witness::read_wtns(&wires, &buffer[..]).unwrap();
+
+        // only the variables found in the constraints are written
+        assert_eq!(read.0.len(), wires.len());
+        for wire in &wires {
+            assert_eq!(read.0.get(wire), witness.0.get(wire));
+        }
+
+        // a `.r1cs` file is not a witness
+        let mut r1cs = vec![];
+        prog.r1cs().write(&mut r1cs).unwrap();
+        assert!(Witness::<FieldPrime>::read_wtns(&wires, &r1cs[..]).is_err());
+
+        // a section larger than the file is not allocated
+        let mut huge_section = buffer.clone();
+        huge_section[16..24].copy_from_slice(&u64::max_value().to_le_bytes());
+        assert_eq!(
+            Witness::<FieldPrime>::read_wtns(&wires, &huge_section[..])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // the last value is not an element of the field
+        let mut out_of_range = buffer.clone();
+        let offset = buffer.len() - 32;
+        out_of_range[offset..].copy_from_slice(&to_bytes(prime::<FieldPrime>(), 32));
+        assert_eq!(
+            Witness::<FieldPrime>::read_wtns(&wires, &out_of_range[..])
+                .unwrap_err()
+                .to_string(),
+            "field element out of range"
+        );
+
+        // the field of the values is only known from the header
+        let values = buffer[buffer.len() - 5 * 32..].to_vec();
+        let mut values_only = vec![];
+        write_sections(
+            &mut values_only,
+            WTNS_MAGIC,
+            WTNS_VERSION,
+            &[(WTNS_VALUES_SECTION, values)],
+        )
+        .unwrap();
+        assert_eq!(
+            Witness::<FieldPrime>::read_wtns(&wires, &values_only[..])
+                .unwrap_err()
+                .to_string(),
+            "missing header section"
+        );
+    }
+
+    #[test]
+    fn write_wires_json() {
+        let prog = prog();
+        let witness = prog
+            .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let mut buffer = vec![];
+        witness
+            .write_wires_json(&prog.r1cs().wires, &mut buffer)
+            .unwrap();
+
+        let json: Value = serde_json::from_slice(&buffer).unwrap();
+
+        // `~one`, `~out_0`, `b`, `a` and `a * a`
+        assert_eq!(json, Value::from(vec!["1", "13", "4", "3", "9"]));
+    }
+
+    #[test]
+    fn import() {
+        let prog = prog();
+        let r1cs = prog.r1cs();
+
+        let mut buffer = vec![];
+        r1cs.write(&mut buffer).unwrap();
+
+        let imported = R1cs::<FieldPrime>::read(&buffer[..]).unwrap();
+
+        // the wires keep their numbers, and the variables are numbered after them
+        assert_eq!(
+            imported.wires,
+            vec![
+                FlatVariable::one(),
+                FlatVariable::public(0),
+                FlatVariable::new(0),
+                FlatVariable::new(1),
+                FlatVariable::new(2),
+            ]
+        );
+        assert_eq!(imported.labels(), r1cs.labels());
+        assert_eq!(
+            (
+                imported.outputs,
+                imported.public_inputs,
+                imported.private_inputs
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(imported.constraints, r1cs.constraints);
+
+        let imported_prog: Prog<FieldPrime> = R1cs::read(&buffer[..]).unwrap().into();
+
+        assert_eq!(imported_prog.private, vec![false, true]);
+        assert_eq!(imported_prog.main.returns, vec![FlatVariable::public(0)]);
+
+        // exporting the imported program gives back the same constraint system
+        assert_eq!(imported_prog.r1cs().constraints, r1cs.constraints);
+
+        // a witness exported for the original program satisfies the imported one
+        let witness = prog
+            .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let mut wtns = vec![];
+        witness.write_wtns(&r1cs.wires, &mut wtns).unwrap();
+
+        let imported_witness = Witness::read_wtns(&imported.wires, &wtns[..]).unwrap();
+
+        assert!(imported_prog.check_witness(&imported_witness).is_ok());
+    }
+
+    #[test]
+    fn import_invalid() {
+        let mut buffer = vec![];
+        prog().r1cs().write(&mut buffer).unwrap();
+
+        // a section larger than the file is not allocated
+        let mut huge_section = buffer.clone();
+        huge_section[16..24].copy_from_slice(&u64::max_value().to_le_bytes());
+        assert_eq!(
+            R1cs::<FieldPrime>::read(&huge_section[..])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // the first coefficient, after the header section and the number of terms and wire of
+        // the first combination, is not an element of the field
+        let mut out_of_range = buffer.clone();
+        let offset = 12 + (12 + 64) + 12 + 8;
+        out_of_range[offset..offset + 32].copy_from_slice(&to_bytes(prime::<FieldPrime>(), 32));
+        assert_eq!(
+            R1cs::<FieldPrime>::read(&out_of_range[..])
+                .unwrap_err()
+                .to_string(),
+            "field element out of range"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Recording of the statements evaluated when executing a program, to follow an execution step by
+//! step
+
+use crate::flat_absy::FlatVariable;
+use crate::ir::{Event, Location, Observer};
+use std::collections::BTreeMap;
+use zokrates_field::field::Field;
+
+/// The statements evaluated during an execution, in order
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    #[serde(skip)]
+    stop_at_failure: bool,
+    #[serde(skip)]
+    names: BTreeMap<FlatVariable, String>,
+}
+
+/// The evaluation of a statement
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Step {
+    /// The index of the statement in the program
+    pub statement: usize,
+    /// Where the statement comes from in the source code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// The source statement it was compiled from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// What evaluating a statement did, with field elements as decimal strings
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Assignment {
+        variable: String,
+        value: String,
+    },
+    Check {
+        left: String,
+        right: String,
+        satisfied: bool,
+    },
+    Directive {
+        helper: String,
+        inputs: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        outputs: Option<Vec<Assignment>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Assignment {
+    pub variable: String,
+    pub value: String,
+}
+
+impl Trace {
+    /// Creates an empty trace. Unless `stop_at_failure` is set, execution goes on after a
+    /// constraint is not satisfied, so that the following statements are recorded as well
+    pub fn new(stop_at_failure: bool) -> Self {
+        Trace {
+            steps: vec![],
+            stop_at_failure,
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// Records the variables found in `names` under their name in the source code instead of
+    /// `_42`
+    pub fn names(mut self, names: BTreeMap<FlatVariable, String>) -> Self {
+        self.names = names;
+        self
+    }
+
+    fn variable(&self, variable: &FlatVariable) -> String {
+        self.names
+            .get(variable)
+            .cloned()
+            .unwrap_or_else(|| variable.to_string())
+    }
+}
+
+impl<T: Field> Observer<T> for Trace {
+    fn observe(&mut self, statement: usize, location: Option<&Location>, event: Event<T>) {
+        let action = match event {
+            Event::Assignment { variable, value } => Action::Assignment {
+                variable: self.variable(variable),
+                value: value.to_dec_string(),
+            },
+            Event::Check { left, right } => Action::Check {
+                left: left.to_dec_string(),
+                right: right.to_dec_string(),
+                satisfied: left == right,
+            },
+            Event::Directive {
+                directive,
+                inputs,
+                outputs,
+            } => Action::Directive {
+                helper: directive.helper.to_string(),
+                inputs: inputs.iter().map(|i| i.to_dec_string()).collect(),
+                outputs: outputs.ok().map(|values| {
+                    directive
+                        .outputs
+                        .iter()
+                        .zip(values)
+                        .map(|(variable, value)| Assignment {
+                            variable: self.variable(variable),
+                            value: value.to_dec_string(),
+                        })
+                        .collect()
+                }),
+                error: outputs.err().map(|e| e.to_string()),
+            },
+        };
+
+        self.steps.push(Step {
+            statement,
+            location: location.map(|l| l.to_string()),
+            source: location.map(|l| l.span.text.clone()),
+            action,
+        });
+    }
+
+    fn keep_going(&self) -> bool {
+        !self.stop_at_failure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flat_absy::FlatVariable;
+    use crate::helpers::{Helper, RustHelper};
+    use crate::ir::{Directive, Error, Function, LinComb, Prog, QuadComb, Statement};
+    use crate::typed_absy::types::Signature;
+    use zokrates_field::field::FieldPrime;
+
+    // def main(private field a):
+    //     b = a * a
+    //     b == 4
+    //     c = b / 0
+    fn prog() -> Prog<FieldPrime> {
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                statements: vec![
+                    Statement::definition(
+                        FlatVariable::new(1),
+                        QuadComb::from_linear_combinations(
+                            FlatVariable::new(0).into(),
+                            FlatVariable::new(0).into(),
+                        ),
+                    ),
+                    Statement::constraint(
+                        FlatVariable::new(1),
+                        LinComb::summand(4, FlatVariable::one()),
+                    ),
+                    Statement::Directive(Directive {
+                        inputs: vec![FlatVariable::new(1).into(), LinComb::zero()],
+                        outputs: vec![FlatVariable::new(2)],
+                        helper: Helper::Rust(RustHelper::Div),
+                    }),
+                ],
+                arguments: vec![FlatVariable::new(0)],
+                returns: vec![],
+                debug: None,
+            },
+            private: vec![true],
+            signature: Signature::new(),
+        }
+    }
+
+    #[test]
+    fn keep_going() {
+        let mut trace = Trace::new(false);
+
+        let result = prog().execute_with(&vec![FieldPrime::from(3)], &mut trace);
+
+        // the division fails after the unsatisfied constraint
+        match result {
+            Err(Error::Solver { message, .. }) => assert_eq!(message, "division by zero"),
+            r => panic!("expected a solver error, got {:?}", r),
+        }
+
+        assert_eq!(
+            trace.steps,
+            vec![
+                Step {
+                    statement: 0,
+                    location: None,
+                    source: None,
+                    action: Action::Assignment {
+                        variable: String::from("_1"),
+                        value: String::from("9")
+                    }
+                },
+                Step {
+                    statement: 1,
+                    location: None,
+                    source: None,
+                    action: Action::Check {
+                        left: String::from("9"),
+                        right: String::from("4"),
+                        satisfied: false
+                    }
+                },
+                Step {
+                    statement: 2,
+                    location: None,
+                    source: None,
+                    action: Action::Directive {
+                        helper: String::from("Rust::Div"),
+                        inputs: vec![String::from("9"), String::from("0")],
+                        outputs: None,
+                        error: Some(String::from("division by zero"))
+                    }
+                },
+            ]
+        );
+
+        assert_eq!(
+            serde_json::to_string(&trace.steps[0]).unwrap(),
+            r#"{"statement":0,"type":"assignment","variable":"_1","value":"9"}"#
+        );
+    }
+
+    #[test]
+    fn stop_at_failure() {
+        let mut trace = Trace::new(true);
+
+        let result = prog().execute_with(&vec![FieldPrime::from(3)], &mut trace);
+
+        match result {
+            Err(Error::UnsatisfiedConstraint { left, right, .. }) => {
+                assert_eq!(left, "9");
+                assert_eq!(right, "4");
+            }
+            r => panic!("expected an unsatisfied constraint, got {:?}", r),
+        }
+
+        assert_eq!(trace.steps.len(), 2);
+    }
+
+    #[test]
+    fn names() {
+        let mut trace = Trace::new(false).names(
+            vec![(FlatVariable::new(1), String::from("b"))]
+                .into_iter()
+                .collect(),
+        );
+
+        let _ = prog().execute_with(&vec![FieldPrime::from(2)], &mut trace);
+
+        assert_eq!(
+            trace.steps[0].action,
+            Action::Assignment {
+                variable: String::from("b"),
+                value: String::from("4")
+            }
+        );
+    }
+}
@@ -38,6 +38,16 @@ impl<T: Field> Witness<T> {
     }
 
     pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.write_with_names(&BTreeMap::new(), writer)
+    }
+
+    /// Writes the witness, with the name of each variable in the source code after its value where
+    /// known. The names are ignored when reading the witness back
+    pub fn write_with_names<W: Write>(
+        &self,
+        names: &BTreeMap<FlatVariable, String>,
+        writer: W,
+    ) -> io::Result<()> {
         let mut wtr = csv::WriterBuilder::new()
             .delimiter(b' ')
             .flexible(true)
@@ -46,7 +56,10 @@ impl<T: Field> Witness<T> {
 
         // Write each line of the witness to the file
         for (variable, value) in &self.0 {
-            wtr.serialize((variable.to_string(), value.to_dec_string()))?;
+            match names.get(variable) {
+                Some(name) => wtr.serialize((variable.to_string(), value.to_dec_string(), name))?,
+                None => wtr.serialize((variable.to_string(), value.to_dec_string()))?,
+            }
         }
 
         Ok(())
@@ -60,9 +73,9 @@ impl<T: Field> Witness<T> {
             .from_reader(&mut reader);
 
         let map = rdr
-            .deserialize::<(String, String)>()
+            .deserialize::<(String, String, Option<String>)>()
             .map(|r| {
-                r.map(|(variable, value)| {
+                r.map(|(variable, value, _)| {
                     let variable =
                         FlatVariable::try_from_human_readable(&variable).map_err(|why| {
                             io::Error::new(
@@ -134,6 +147,36 @@ mod tests {
             assert_eq!(w, r);
         }
 
+        #[test]
+        fn serialize_deserialize_with_names() {
+            let w = Witness(
+                vec![
+                    (FlatVariable::new(42), FieldPrime::from(42)),
+                    (FlatVariable::new(43), FieldPrime::from(43)),
+                ]
+                .into_iter()
+                .collect(),
+            );
+
+            let names = vec![(FlatVariable::new(42), String::from("a[1]"))]
+                .into_iter()
+                .collect();
+
+            let mut buff = Cursor::new(vec![]);
+
+            w.write_with_names(&names, &mut buff).unwrap();
+            assert_eq!(
+                String::from_utf8(buff.get_ref().clone()).unwrap(),
+                "_42 42 a[1]\n_43 43\n"
+            );
+
+            buff.set_position(0);
+
+            let r = Witness::read(buff).unwrap();
+
+            assert_eq!(w, r);
+        }
+
         #[test]
         fn wrong_value() {
             let mut buff = Cursor::new(vec![]);
@@ -10,6 +10,7 @@ extern crate typed_arena;
 extern crate serde_derive;
 extern crate bellman_ce as bellman;
 extern crate bincode;
+extern crate blake2_rfc_bellman_edition as blake2_rfc;
 extern crate ff_ce as ff;
 extern crate lazy_static;
 extern crate pairing_ce as pairing;
@@ -78,6 +78,7 @@ mod tests {
                 ],
                 returns: vec![],
                 arguments: vec![],
+                debug: None,
             },
             signature: Signature::new(),
         };
@@ -118,6 +119,7 @@ mod tests {
                 ],
                 returns: vec![],
                 arguments: vec![],
+                debug: None,
             },
             signature: Signature::new(),
         };
@@ -138,6 +140,7 @@ mod tests {
                 ],
                 returns: vec![],
                 arguments: vec![],
+                debug: None,
             },
             signature: Signature::new(),
         };
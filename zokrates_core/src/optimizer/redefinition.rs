@@ -162,6 +162,7 @@ mod tests {
             arguments: vec![x],
             statements: vec![Statement::definition(y, x), Statement::definition(z, y)],
             returns: vec![z.into()],
+            debug: None,
         };
 
         let optimized: Function<FieldPrime> = Function {
@@ -169,6 +170,7 @@ mod tests {
             arguments: vec![x],
             statements: vec![Statement::definition(z, x)],
             returns: vec![z],
+            debug: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::new();
@@ -189,6 +191,7 @@ mod tests {
             arguments: vec![x],
             statements: vec![Statement::definition(one, x)],
             returns: vec![x.into()],
+            debug: None,
         };
 
         let optimized = f.clone();
@@ -224,6 +227,7 @@ mod tests {
                 Statement::constraint(z, y),
             ],
             returns: vec![z.into()],
+            debug: None,
         };
 
         let optimized: Function<FieldPrime> = Function {
@@ -231,6 +235,7 @@ mod tests {
             arguments: vec![x],
             statements: vec![Statement::definition(z, x), Statement::constraint(z, x)],
             returns: vec![z.into()],
+            debug: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::new();
@@ -267,6 +272,7 @@ mod tests {
                 Statement::definition(w, t),
             ],
             returns: vec![z, w],
+            debug: None,
         };
 
         let optimized: Function<FieldPrime> = Function {
@@ -277,6 +283,7 @@ mod tests {
                 Statement::definition(w, FieldPrime::from(1)),
             ],
             returns: vec![z, w],
+            debug: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::new();
@@ -321,6 +328,7 @@ mod tests {
                 Statement::definition(r, LinComb::from(a) + LinComb::from(b) + LinComb::from(c)),
             ],
             returns: vec![r],
+            debug: None,
         };
 
         let optimized: Function<FieldPrime> = Function {
@@ -334,6 +342,7 @@ mod tests {
                 Statement::definition(r, LinComb::summand(6, x) + LinComb::summand(6, y)),
             ],
             returns: vec![r],
+            debug: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::new();
@@ -369,6 +378,7 @@ mod tests {
                 Statement::definition(z, LinComb::from(x)),
             ],
             returns: vec![],
+            debug: None,
         };
 
         let optimized = f.clone();
@@ -398,6 +408,7 @@ mod tests {
                 Statement::constraint(x, FieldPrime::from(2)),
             ],
             returns: vec![x.into()],
+            debug: None,
         };
 
         let optimized = f.clone();
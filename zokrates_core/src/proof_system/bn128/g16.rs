@@ -1,300 +1,353 @@
 use crate::ir;
-use crate::proof_system::bn128::utils::bellman::Computation;
+use crate::proof_system::bn128::mpc::MPCParameters;
+use crate::proof_system::bn128::phase1::Phase1;
+use crate::proof_system::bn128::types::g16::{ProofPoints, VerificationKey};
+use crate::proof_system::bn128::types::Proof;
+use crate::proof_system::bn128::utils::bellman::{batch_verify, Computation};
+use crate::proof_system::bn128::utils::java::{
+    java_entry_point, java_g1, java_g1_bytes, java_g2, java_g2_bytes, JavaNames, JavaProof,
+};
 use crate::proof_system::bn128::utils::solidity::{
-    SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, SOLIDITY_PAIRING_LIB_V2,
+    solidity_g1, solidity_g2, SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB,
+    SOLIDITY_PAIRING_LIB_V2,
 };
 
-use crate::proof_system::ProofSystem;
-use bellman::groth16::Parameters;
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use zokrates_field::field::FieldPrime;
+use crate::proof_system::{Error, ProofSystem, SetupKeypair};
+use bellman::groth16::{prepare_verifying_key, verify_proof, Parameters};
+use pairing::bn256::Bn256;
+use std::sync::Arc;
+use std::thread;
+use zokrates_field::field::{Field, FieldPrime};
 
 const G16_WARNING: &str = "WARNING: You are using the G16 scheme which is subject to malleability. See zokrates.github.io/reference/proving_schemes.html#g16-malleability for implications.";
 
 pub struct G16 {}
+
+/// A G16 proving key parsed from its binary format, so that it can be used for many proofs
+/// without reading it again
+pub struct G16ProvingKey(Parameters<Bn256>);
+
+impl G16 {
+    /// Performs the setup with the circuit-independent parameters taken from a Powers of Tau
+    /// ceremony, so that only the circuit-specific randomness is sampled locally
+    pub fn setup_with_phase1(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        phase1: &Phase1,
+    ) -> Result<SetupKeypair, Error> {
+        println!("{}", G16_WARNING);
+
+        let mut params = MPCParameters::from_phase1(program, phase1)?;
+        params.contribute(&[])?;
+        params.keypair()
+    }
+
+    /// Parses a proving key in the format written by `setup`, to be used with
+    /// `generate_proof_with_key`
+    pub fn read_proving_key(&self, pk: &[u8]) -> Result<G16ProvingKey, Error> {
+        Parameters::read(pk, true)
+            .map(G16ProvingKey)
+            .map_err(|e| Error::MalformedKey(e.to_string()))
+    }
+
+    /// Generates a proof using a proving key which was already parsed
+    pub fn generate_proof_with_key(
+        &self,
+        program: &ir::Prog<FieldPrime>,
+        witness: ir::Witness<FieldPrime>,
+        pk: &G16ProvingKey,
+    ) -> Result<String, Error> {
+        std::env::set_var("BELLMAN_VERBOSE", "0");
+
+        program.check_witness(&witness)?;
+
+        prove(program, &pk.0, witness)
+    }
+
+    /// Generates a proof for each witness, reading the proving key only once. The witnesses are
+    /// split between `threads` threads, and never more threads than witnesses. Each proof is
+    /// already computed on all cores by bellman's own thread pool, so more threads mostly help
+    /// when the witnesses are many and the program is small. Returns, for each witness, the proof
+    /// or why it failed
+    pub fn generate_proofs(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        witnesses: Vec<ir::Witness<FieldPrime>>,
+        pk: &[u8],
+        threads: usize,
+    ) -> Result<Vec<Result<String, Error>>, Error> {
+        std::env::set_var("BELLMAN_VERBOSE", "0");
+
+        println!("{}", G16_WARNING);
+
+        let params = Arc::new(self.read_proving_key(pk)?);
+        let program = Arc::new(program);
+
+        let threads = threads.min(witnesses.len()).max(1);
+        let chunk_size = (witnesses.len() + threads - 1) / threads;
+        let mut witnesses = witnesses.into_iter();
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let chunk: Vec<_> = witnesses.by_ref().take(chunk_size).collect();
+                let program = program.clone();
+                let params = params.clone();
+
+                thread::spawn(move || {
+                    chunk
+                        .into_iter()
+                        .map(|witness| -> Result<String, Error> {
+                            program.check_witness(&witness)?;
+                            prove(&program, &params.0, witness)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .map_err(|_| Error::Backend(String::from("proving thread panicked")))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|proofs| proofs.into_iter().flatten().collect())
+    }
+
+    /// Verifies many proofs against the same verification key using randomized batch pairing
+    /// checks. Returns, for each proof, whether it is valid, or why it could not be read
+    pub fn verify_batch(
+        &self,
+        vk: &str,
+        proofs: &[String],
+    ) -> Result<Vec<Result<bool, Error>>, Error> {
+        let vk = serialize::deserialize_vk(&VerificationKey::read(vk.as_bytes())?)?;
+
+        let proofs: Vec<_> = proofs
+            .iter()
+            .map(|p| serialize::deserialize_proof(&Proof::read(p.as_bytes())?))
+            .collect();
+
+        let parsed: Vec<_> = proofs
+            .iter()
+            .filter_map(|p| p.as_ref().ok().cloned())
+            .collect();
+
+        let mut valid = batch_verify(&vk, &parsed).into_iter();
+
+        Ok(proofs
+            .into_iter()
+            .map(|p| p.map(|_| valid.next().unwrap()))
+            .collect())
+    }
+}
+
 impl ProofSystem for G16 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(&self, program: ir::Prog<FieldPrime>) -> Result<SetupKeypair, Error> {
         std::env::set_var("BELLMAN_VERBOSE", "0");
 
         println!("{}", G16_WARNING);
 
-        let parameters = Computation::without_witness(program).setup();
-        let parameters_file = File::create(PathBuf::from(pk_path)).unwrap();
-        parameters.write(parameters_file).unwrap();
-        let mut vk_file = File::create(PathBuf::from(vk_path)).unwrap();
-        vk_file
-            .write(serialize::serialize_vk(parameters.vk).as_ref())
-            .unwrap();
+        let parameters = Computation::without_witness(&program).setup()?;
+        let mut pk = vec![];
+        parameters.write(&mut pk)?;
+
+        Ok(SetupKeypair {
+            vk: serialize::serialize_vk(&parameters.vk).to_json(),
+            pk,
+        })
     }
 
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
         witness: ir::Witness<FieldPrime>,
-        pk_path: &str,
-        proof_path: &str,
-    ) -> bool {
+        pk: &[u8],
+    ) -> Result<String, Error> {
         std::env::set_var("BELLMAN_VERBOSE", "0");
 
         println!("{}", G16_WARNING);
 
-        let computation = Computation::with_witness(program, witness);
-        let parameters_file = File::open(PathBuf::from(pk_path)).unwrap();
+        program.check_witness(&witness)?;
 
-        let params = Parameters::read(parameters_file, true).unwrap();
+        let params = Parameters::read(pk, true).map_err(|e| Error::MalformedKey(e.to_string()))?;
 
-        let proof = computation.clone().prove(&params);
+        prove(&program, &params, witness)
+    }
 
-        let mut proof_file = File::create(PathBuf::from(proof_path)).unwrap();
-        write!(
-            proof_file,
-            "{}",
-            serialize::serialize_proof(&proof, &computation.public_inputs_values())
-        )
-        .unwrap();
-        true
+    fn verify(
+        &self,
+        vk: &str,
+        proof: &str,
+        inputs: Option<Vec<FieldPrime>>,
+    ) -> Result<bool, Error> {
+        let vk = serialize::deserialize_vk(&VerificationKey::read(vk.as_bytes())?)?;
+        let (proof, proof_inputs) = serialize::deserialize_proof(&Proof::read(proof.as_bytes())?)?;
+
+        // expected inputs take precedence over the ones embedded in the proof
+        let inputs: Vec<_> = inputs
+            .map(|inputs| inputs.into_iter().map(|i| i.into_bellman()).collect())
+            .unwrap_or(proof_inputs);
+
+        if inputs.len() + 1 != vk.ic.len() {
+            return Ok(false);
+        }
+
+        let pvk = prepare_verifying_key(&vk);
+
+        Ok(verify_proof(&pvk, &proof, &inputs)?)
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>, is_abiv2: bool) -> String {
-        let mut lines = reader.lines();
+    fn export_solidity_verifier(&self, vk: &str, is_abiv2: bool) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
 
-        let (mut template_text, solidity_pairing_lib) = if is_abiv2 {
-            (
-                String::from(CONTRACT_TEMPLATE_V2),
-                String::from(SOLIDITY_PAIRING_LIB_V2),
-            )
+        let (template, solidity_pairing_lib) = if is_abiv2 {
+            (CONTRACT_TEMPLATE_V2, SOLIDITY_PAIRING_LIB_V2)
         } else {
-            (
-                String::from(CONTRACT_TEMPLATE),
-                String::from(SOLIDITY_PAIRING_LIB),
-            )
+            (CONTRACT_TEMPLATE, SOLIDITY_PAIRING_LIB)
         };
 
-        let gamma_abc_template = String::from("vk.gamma_abc[index] = Pairing.G1Point(points);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_gamma_abc_len_regex = Regex::new(r#"(<%vk_gamma_abc_length%>)"#).unwrap();
-        let vk_gamma_abc_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_gamma_abc_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_gamma_abc_repeat_regex = Regex::new(r#"(<%vk_gamma_abc_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
-
-        for _ in 0..4 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
-        }
+        let gamma_abc_pts = vk
+            .gamma_abc
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("vk.gamma_abc[{}] = Pairing.G1Point({});", i, solidity_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let template_text = template
+            .replace("<%vk_a%>", &solidity_g1(&vk.alpha))
+            .replace("<%vk_b%>", &solidity_g2(&vk.beta))
+            .replace("<%vk_gamma%>", &solidity_g2(&vk.gamma))
+            .replace("<%vk_delta%>", &solidity_g2(&vk.delta))
+            .replace("<%vk_gamma_abc_length%>", &vk.gamma_abc.len().to_string())
+            .replace("<%vk_gamma_abc_pts%>", &gamma_abc_pts)
+            .replace("<%vk_input_length%>", &(vk.gamma_abc.len() - 1).to_string());
+
+        Ok(format!(
+            "{}{}{}",
+            SOLIDITY_G2_ADDITION_LIB, solidity_pairing_lib, template_text
+        ))
+    }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let gamma_abc_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_gamma_abc_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", gamma_abc_count).as_str(),
-            )
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", gamma_abc_count - 1).as_str(),
-            )
-            .into_owned();
-
-        let mut gamma_abc_repeat_text = String::new();
-        for x in 0..gamma_abc_count {
-            let mut curr_template = gamma_abc_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_gamma_abc_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_gamma_abc_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            gamma_abc_repeat_text.push_str(curr_template.as_str());
-            if x < gamma_abc_count - 1 {
-                gamma_abc_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_gamma_abc_repeat_regex
-            .replace(template_text.as_str(), gamma_abc_repeat_text.as_str())
-            .into_owned();
+    fn export_avm_verifier(
+        &self,
+        vk: &str,
+        names: &JavaNames,
+        is_callable: bool,
+    ) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
+
+        let gamma_abc_pts = vk
+            .gamma_abc
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("gamma_abc[{}] = new G1Point({});", i, java_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let verifier = CONTRACT_AVM_TEMPLATE
+            .replace("<%vk_alpha%>", &java_g1(&vk.alpha))
+            .replace("<%vk_beta%>", &java_g2(&vk.beta))
+            .replace("<%vk_gamma%>", &java_g2(&vk.gamma))
+            .replace("<%vk_delta%>", &java_g2(&vk.delta))
+            .replace("<%vk_gamma_abc_length%>", &vk.gamma_abc.len().to_string())
+            .replace("<%vk_gamma_abc_pts%>", &gamma_abc_pts);
+
+        Ok(names.apply(&java_entry_point(&verifier, is_callable)))
+    }
 
-        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+    fn export_avm_proof(&self, proof: &str) -> Result<JavaProof, Error> {
+        let proof: Proof<ProofPoints> = Proof::read(proof.as_bytes())?;
 
-        format!(
-            "{}{}{}",
-            SOLIDITY_G2_ADDITION_LIB, solidity_pairing_lib, template_text
+        JavaProof::new(
+            &[
+                java_g1_bytes(&proof.proof.a)?,
+                java_g2_bytes(&proof.proof.b)?,
+                java_g1_bytes(&proof.proof.c)?,
+            ],
+            &proof.inputs,
         )
     }
+}
 
-    fn export_avm_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
-        let mut template_text = String::from(CONTRACT_AVM_TEMPLATE);
-
-        
-        let gamma_abc_template = String::from("gamma_abc[index] = new G1Point(coord, coord);"); //copy this for each entry
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_gamma_abc_len_regex = Regex::new(r#"(<%vk_gamma_abc_length%>)"#).unwrap();
-        let vk_gamma_abc_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_gamma_abc_points_regex = Regex::new(r#"coord"#).unwrap();
-        let vk_gamma_abc_repeat_regex = Regex::new(r#"(<%vk_gamma_abc_pts%>)"#).unwrap();
-
-        let vk_value = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        for value in vk_value.find_iter(current_line_split[1]) {
-            template_text = vk_regex
-                .replace(template_text.as_str(), value.as_str())
-                .into_owned();
-        }
+fn prove(
+    program: &ir::Prog<FieldPrime>,
+    params: &Parameters<Bn256>,
+    witness: ir::Witness<FieldPrime>,
+) -> Result<String, Error> {
+    let computation = Computation::with_witness(&program, witness);
 
-        for _ in 0..3 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
+    let public_inputs = computation.public_inputs_values();
+    let proof = computation.prove(params)?;
 
-            let mut values = Vec::new();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                values.push(value.as_str());
-            }
-            let order: [usize;4] = [1,0,3,2];
-            for i in &order {
-                template_text = vk_regex
-                    .replace(template_text.as_str(), values[*i])
-                    .into_owned();
-            }
-        }
-
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let gamma_abc_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_gamma_abc_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", gamma_abc_count).as_str(),
-            )
-            .into_owned();
-
-        let mut gamma_abc_repeat_text = String::new();
-        for x in 0..gamma_abc_count {
-            let mut curr_template = gamma_abc_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_gamma_abc_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                curr_template = vk_gamma_abc_points_regex
-                .replace(curr_template.as_str(), value.as_str())
-                .into_owned();
-            }
+    Ok(serialize::serialize_proof(&proof, &public_inputs).to_json())
+}
 
-            gamma_abc_repeat_text.push_str(curr_template.as_str());
-            if x < gamma_abc_count - 1 {
-                gamma_abc_repeat_text.push_str("\n        ");
-            }
+pub(super) mod serialize {
+
+    use crate::proof_system::bn128::types::g16::{ProofPoints, VerificationKey};
+    use crate::proof_system::bn128::types::{G1Affine, G2Affine, Proof};
+    use crate::proof_system::bn128::utils::bellman::parse_fr;
+    use crate::proof_system::Error;
+    use bellman::groth16::{Proof as BellmanProof, VerifyingKey};
+    use pairing::bn256::{self, Bn256, Fr};
+    use pairing::CurveAffine;
+
+    pub fn serialize_vk(vk: &VerifyingKey<Bn256>) -> VerificationKey {
+        VerificationKey {
+            alpha: G1Affine::from_bellman(&vk.alpha_g1),
+            beta: G2Affine::from_bellman(&vk.beta_g2),
+            gamma: G2Affine::from_bellman(&vk.gamma_g2),
+            delta: G2Affine::from_bellman(&vk.delta_g2),
+            gamma_abc: vk.ic.iter().map(G1Affine::from_bellman).collect(),
         }
+    }
 
-        template_text = vk_gamma_abc_repeat_regex
-            .replace(template_text.as_str(), gamma_abc_repeat_text.as_str())
-            .into_owned();
-
-        let re = Regex::new(r"0[xX](?P<v>[0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "\"$v\"").to_string();
-        format!("{}", template_text)
+    pub fn deserialize_vk(vk: &VerificationKey) -> Result<VerifyingKey<Bn256>, Error> {
+        deserialize_vk_points(vk).map_err(Error::MalformedKey)
     }
-}
 
-mod serialize {
-
-    use crate::proof_system::bn128::utils::bellman::{
-        parse_fr_json, parse_g1_hex, parse_g1_json, parse_g2_hex, parse_g2_json,
-    };
-    use bellman::groth16::{Proof, VerifyingKey};
-    use pairing::bn256::{Bn256, Fr};
-
-    pub fn serialize_vk(vk: VerifyingKey<Bn256>) -> String {
-        format!(
-            "vk.alpha = {}
-    vk.beta = {}
-    vk.gamma = {}
-    vk.delta = {}
-    vk.gamma_abc.len() = {}
-    {}",
-            parse_g1_hex(&vk.alpha_g1),
-            parse_g2_hex(&vk.beta_g2),
-            parse_g2_hex(&vk.gamma_g2),
-            parse_g2_hex(&vk.delta_g2),
-            vk.ic.len(),
-            vk.ic
+    fn deserialize_vk_points(vk: &VerificationKey) -> Result<VerifyingKey<Bn256>, String> {
+        Ok(VerifyingKey {
+            alpha_g1: vk.alpha.to_bellman()?,
+            beta_g2: vk.beta.to_bellman()?,
+            gamma_g2: vk.gamma.to_bellman()?,
+            delta_g2: vk.delta.to_bellman()?,
+            ic: vk
+                .gamma_abc
                 .iter()
-                .enumerate()
-                .map(|(i, x)| format!("vk.gamma_abc[{}] = {}", i, parse_g1_hex(x)))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
+                .map(G1Affine::to_bellman)
+                .collect::<Result<_, _>>()?,
+            // beta and delta in G1 are only used for proving and are not part of the exported key
+            beta_g1: bn256::G1Affine::zero(),
+            delta_g1: bn256::G1Affine::zero(),
+        })
     }
 
-    pub fn serialize_proof(p: &Proof<Bn256>, inputs: &Vec<Fr>) -> String {
-        format!(
-            "{{
-        \"proof\": {{
-            \"a\": {},
-            \"b\": {},
-            \"c\": {}
-        }},
-        \"inputs\": [{}]
-    }}",
-            parse_g1_json(&p.a),
-            parse_g2_json(&p.b),
-            parse_g1_json(&p.c),
-            inputs
-                .iter()
-                .map(parse_fr_json)
-                .collect::<Vec<_>>()
-                .join(", "),
+    pub fn serialize_proof(p: &BellmanProof<Bn256>, inputs: &Vec<Fr>) -> Proof<ProofPoints> {
+        Proof::new(
+            ProofPoints {
+                a: G1Affine::from_bellman(&p.a),
+                b: G2Affine::from_bellman(&p.b),
+                c: G1Affine::from_bellman(&p.c),
+            },
+            inputs.iter().map(parse_fr).collect(),
         )
     }
+
+    pub fn deserialize_proof(
+        p: &Proof<ProofPoints>,
+    ) -> Result<(BellmanProof<Bn256>, Vec<Fr>), Error> {
+        let proof = BellmanProof {
+            a: p.proof.a.to_bellman().map_err(Error::MalformedProof)?,
+            b: p.proof.b.to_bellman().map_err(Error::MalformedProof)?,
+            c: p.proof.c.to_bellman().map_err(Error::MalformedProof)?,
+        };
+
+        Ok((proof, p.inputs_to_bellman()?))
+    }
 }
 
 const CONTRACT_TEMPLATE_V2: &str = r#"
@@ -425,7 +478,7 @@ contract Verifier {
 
 
 const CONTRACT_AVM_TEMPLATE: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import avm.Blockchain;
 import org.aion.avm.tooling.abi.Callable;
@@ -437,7 +490,7 @@ import java.util.Arrays;
  * Verifier smart contract. Auto-generated by Zokrates.
  */
 @SuppressWarnings({"WeakerAccess", "unused"})
-public class Verifier {
+public class <%class%> {
     protected static class VerifyingKey {
         public final G1Point alpha;
         public final G2Point beta;
@@ -492,10 +545,10 @@ public class Verifier {
     }
 
     protected static VerifyingKey verifyingKey() {
-        G1Point alpha = new G1Point(<%vk_ax%>, <%vk_ay%>);
-        G2Point beta = new G2Point(<%vk_bxx%>, <%vk_bxy%>, <%vk_byx%>, <%vk_byy%>);
-        G2Point gamma = new G2Point(<%vk_gammaxx%>, <%vk_gammaxy%>, <%vk_gammayx%>, <%vk_gammayy%>);
-        G2Point delta = new G2Point(<%vk_deltaxx%>, <%vk_deltaxy%>, <%vk_deltayx%>, <%vk_deltayy%>);
+        G1Point alpha = new G1Point(<%vk_alpha%>);
+        G2Point beta = new G2Point(<%vk_beta%>);
+        G2Point gamma = new G2Point(<%vk_gamma%>);
+        G2Point delta = new G2Point(<%vk_delta%>);
 
         G1Point[] gamma_abc = new G1Point[<%vk_gamma_abc_length%>];
         <%vk_gamma_abc_pts%>
@@ -534,24 +587,7 @@ public class Verifier {
 
         return true;
     }
-
-    @Callable
-    public static boolean verify(BigInteger[] input, byte[] proof) {
-        Blockchain.println("verify() called");
-
-        try {
-            if (verify(input, Proof.deserialize(proof))) {
-                Blockchain.log("VerifySnark".getBytes(), BigInteger.ONE.toByteArray());
-                return true;
-            }
-        } catch (Exception e) {
-            Blockchain.println("verify() failed with exception: " + e.getMessage());
-        }
-
-        Blockchain.log("VerifySnark".getBytes(), BigInteger.ZERO.toByteArray());
-        return false;
-    }
-}
+<%entry_point%>}
 "#;
 
 #[cfg(test)]
@@ -562,10 +598,8 @@ mod tests {
 
         mod proof {
             use super::*;
-            use crate::flat_absy::FlatVariable;
-            use crate::ir::*;
             use crate::proof_system::bn128::g16::serialize::serialize_proof;
-            use typed_absy::types::{Signature, Type};
+            use crate::proof_system::bn128::tests::program;
 
             #[allow(dead_code)]
             #[derive(Deserialize)]
@@ -584,36 +618,213 @@ mod tests {
 
             #[test]
             fn serialize() {
-                let program: Prog<FieldPrime> = Prog {
-                    main: Function {
-                        id: String::from("main"),
-                        arguments: vec![FlatVariable::new(0)],
-                        returns: vec![FlatVariable::public(0)],
-                        statements: vec![Statement::Constraint(
-                            FlatVariable::new(0).into(),
-                            FlatVariable::public(0).into(),
-                        )],
-                    },
-                    private: vec![false],
-                    signature: Signature::new()
-                        .inputs(vec![Type::FieldElement])
-                        .outputs(vec![Type::FieldElement]),
-                };
+                let program = program();
 
                 let witness = program
                     .clone()
                     .execute(&vec![FieldPrime::from(42)])
                     .unwrap();
-                let computation = Computation::with_witness(program, witness);
+                let computation = Computation::with_witness(&program, witness);
 
                 let public_inputs_values = computation.public_inputs_values();
 
-                let params = computation.clone().setup();
-                let proof = computation.prove(&params);
+                let params = computation.clone().setup().unwrap();
+                let proof = computation.prove(&params).unwrap();
 
-                let serialized_proof = serialize_proof(&proof, &public_inputs_values);
+                let serialized_proof = serialize_proof(&proof, &public_inputs_values).to_json();
                 serde_json::from_str::<G16Proof>(&serialized_proof).unwrap();
             }
+
+            #[test]
+            fn serialize_deserialize_verify() {
+                use crate::proof_system::bn128::g16::serialize::{
+                    deserialize_proof, deserialize_vk, serialize_vk,
+                };
+
+                let program = program();
+
+                let witness = program
+                    .clone()
+                    .execute(&vec![FieldPrime::from(42)])
+                    .unwrap();
+                let computation = Computation::with_witness(&program, witness);
+
+                let public_inputs_values = computation.public_inputs_values();
+
+                let params = computation.clone().setup().unwrap();
+                let proof = computation.prove(&params).unwrap();
+
+                let vk = deserialize_vk(&serialize_vk(&params.vk)).unwrap();
+                assert_eq!(vk.alpha_g1, params.vk.alpha_g1);
+                assert_eq!(vk.ic, params.vk.ic);
+
+                let (deserialized_proof, inputs) =
+                    deserialize_proof(&serialize_proof(&proof, &public_inputs_values)).unwrap();
+                assert_eq!(deserialized_proof, proof);
+                assert_eq!(inputs, public_inputs_values);
+
+                let pvk = prepare_verifying_key(&vk);
+                assert!(verify_proof(&pvk, &deserialized_proof, &inputs).unwrap());
+                assert!(!verify_proof(
+                    &pvk,
+                    &deserialized_proof,
+                    &[FieldPrime::from(41).into_bellman(), inputs[1]]
+                )
+                .unwrap());
+            }
+        }
+    }
+
+    mod proving {
+        use super::*;
+        use crate::flat_absy::FlatVariable;
+        use crate::ir::*;
+        use crate::proof_system::bn128::tests::program;
+        use std::collections::BTreeMap;
+
+        #[test]
+        fn unsatisfied_witness() {
+            let program = program();
+
+            let witness = Witness(
+                vec![
+                    (FlatVariable::one(), FieldPrime::from(1)),
+                    (FlatVariable::new(0), FieldPrime::from(42)),
+                    (FlatVariable::public(0), FieldPrime::from(41)),
+                ]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            );
+
+            // the witness is checked before the proving key is read, so we can pass an empty one
+            let result = G16 {}.generate_proof(program, witness, &[]);
+
+            match result {
+                Err(Error::UnsatisfiedConstraint { left, right, .. }) => {
+                    assert_eq!(left, "42");
+                    assert_eq!(right, "41");
+                }
+                r => panic!("expected an unsatisfied constraint, got {:?}", r),
+            }
+        }
+
+        #[test]
+        fn setup_prove_verify() {
+            let program = program();
+
+            let witness = program
+                .clone()
+                .execute(&vec![FieldPrime::from(42)])
+                .unwrap();
+
+            let keypair = G16 {}.setup(program.clone()).unwrap();
+            let proof = G16 {}
+                .generate_proof(program, witness, &keypair.pk)
+                .unwrap();
+
+            assert!(G16 {}.verify(&keypair.vk, &proof, None).unwrap());
+            assert!(!G16 {}
+                .verify(
+                    &keypair.vk,
+                    &proof,
+                    Some(vec![FieldPrime::from(41), FieldPrime::from(42)])
+                )
+                .unwrap());
+        }
+
+        #[test]
+        fn verify_batch() {
+            let program = program();
+
+            let keypair = G16 {}.setup(program.clone()).unwrap();
+            let other_keypair = G16 {}.setup(program.clone()).unwrap();
+
+            let prove = |pk: &[u8], input: u32| {
+                let witness = program
+                    .clone()
+                    .execute(&vec![FieldPrime::from(input)])
+                    .unwrap();
+                G16 {}.generate_proof(program.clone(), witness, pk).unwrap()
+            };
+
+            let proofs = vec![
+                prove(&keypair.pk, 42),
+                prove(&other_keypair.pk, 42),
+                String::from("not a proof"),
+                prove(&keypair.pk, 7),
+            ];
+
+            let result = G16 {}.verify_batch(&keypair.vk, &proofs).unwrap();
+
+            assert_eq!(result.len(), 4);
+            assert_eq!(result[0].as_ref().ok(), Some(&true));
+            assert_eq!(result[1].as_ref().ok(), Some(&false));
+            assert!(result[2].is_err());
+            assert_eq!(result[3].as_ref().ok(), Some(&true));
+        }
+
+        #[test]
+        fn generate_proofs() {
+            let program = program();
+
+            let keypair = G16 {}.setup(program.clone()).unwrap();
+
+            let unsatisfied = Witness(
+                vec![
+                    (FlatVariable::one(), FieldPrime::from(1)),
+                    (FlatVariable::new(0), FieldPrime::from(42)),
+                    (FlatVariable::public(0), FieldPrime::from(41)),
+                ]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            );
+
+            let witnesses = vec![
+                program.execute(&vec![FieldPrime::from(42)]).unwrap(),
+                unsatisfied,
+                program.execute(&vec![FieldPrime::from(7)]).unwrap(),
+            ];
+
+            let proofs = G16 {}
+                .generate_proofs(program.clone(), witnesses, &keypair.pk, 2)
+                .unwrap();
+
+            assert_eq!(proofs.len(), 3);
+            assert!(G16 {}
+                .verify(&keypair.vk, proofs[0].as_ref().unwrap(), None)
+                .unwrap());
+            match proofs[1] {
+                Err(Error::UnsatisfiedConstraint { .. }) => {}
+                ref r => panic!("expected an unsatisfied constraint, got {:?}", r),
+            }
+            assert!(G16 {}
+                .verify(&keypair.vk, proofs[2].as_ref().unwrap(), None)
+                .unwrap());
+
+            // there are never more threads than witnesses
+            assert!(G16 {}
+                .generate_proofs(program, vec![], &keypair.pk, 4)
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn generate_proof_with_key() {
+            let program = program();
+
+            let keypair = G16 {}.setup(program.clone()).unwrap();
+            let pk = G16 {}.read_proving_key(&keypair.pk).unwrap();
+
+            for i in 0..2 {
+                let witness = program.execute(&vec![FieldPrime::from(i)]).unwrap();
+                let proof = G16 {}
+                    .generate_proof_with_key(&program, witness, &pk)
+                    .unwrap();
+
+                assert!(G16 {}.verify(&keypair.vk, &proof, None).unwrap());
+            }
+
+            assert!(G16 {}.read_proving_key(&[]).is_err());
         }
     }
 }
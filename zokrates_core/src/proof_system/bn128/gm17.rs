@@ -2,16 +2,24 @@ extern crate libc;
 
 use self::libc::{c_char, c_int};
 use ir;
-use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup};
+use pairing::bn256::Fr;
+use proof_system::bn128::types::gm17::{ProofPoints, VerificationKey};
+use proof_system::bn128::types::Proof;
+use proof_system::bn128::utils::bellman::{
+    accumulate_inputs, g1_add, g1_neg, g2_add, pairing_product_is_one,
+};
+use proof_system::bn128::utils::java::{
+    java_entry_point, java_g1, java_g1_bytes, java_g2, java_g2_bytes, JavaNames, JavaProof,
+};
+use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup, ScratchDir};
 use proof_system::bn128::utils::solidity::{
-    SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, SOLIDITY_PAIRING_LIB_V2,
+    solidity_g1, solidity_g2, SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB,
+    SOLIDITY_PAIRING_LIB_V2,
 };
-use proof_system::ProofSystem;
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use proof_system::{Error, ProofSystem, SetupKeypair};
+use std::fs::{self, File};
 
-use zokrates_field::field::FieldPrime;
+use zokrates_field::field::{Field, FieldPrime};
 
 pub struct GM17 {}
 
@@ -47,7 +55,11 @@ extern "C" {
 }
 
 impl ProofSystem for GM17 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(&self, program: ir::Prog<FieldPrime>) -> Result<SetupKeypair, Error> {
+        let scratch = ScratchDir::new()?;
+        let pk_path = scratch.path("proving.key");
+        let vk_path = scratch.path("verification.key");
+
         let (
             a_arr,
             b_arr,
@@ -60,9 +72,9 @@ impl ProofSystem for GM17 {
             num_inputs,
             pk_path_cstring,
             vk_path_cstring,
-        ) = prepare_setup(program, pk_path, vk_path);
+        ) = prepare_setup(program, &pk_path, &vk_path)?;
 
-        unsafe {
+        let done = unsafe {
             _gm17_setup(
                 a_arr.as_ptr(),
                 b_arr.as_ptr(),
@@ -75,17 +87,32 @@ impl ProofSystem for GM17 {
                 num_inputs as i32,
                 pk_path_cstring.as_ptr(),
                 vk_path_cstring.as_ptr(),
-            );
+            )
+        };
+
+        if !done {
+            return Err(Error::Backend(String::from("libsnark setup failed")));
         }
+
+        // libsnark writes the verification key in the legacy text format, which we convert to json
+        Ok(SetupKeypair {
+            vk: VerificationKey::read(File::open(&vk_path)?)?.to_json(),
+            pk: fs::read(&pk_path)?,
+        })
     }
 
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
         witness: ir::Witness<FieldPrime>,
-        pk_path: &str,
-        proof_path: &str,
-    ) -> bool {
+        pk: &[u8],
+    ) -> Result<String, Error> {
+        let scratch = ScratchDir::new()?;
+        let pk_path = scratch.path("proving.key");
+        let proof_path = scratch.path("proof.json");
+
+        fs::write(&pk_path, pk)?;
+
         let (
             pk_path_cstring,
             proof_path_cstring,
@@ -93,9 +120,9 @@ impl ProofSystem for GM17 {
             public_inputs_length,
             private_inputs_arr,
             private_inputs_length,
-        ) = prepare_generate_proof(program, witness, pk_path, proof_path);
+        ) = prepare_generate_proof(program, witness, &pk_path, &proof_path)?;
 
-        unsafe {
+        let done = unsafe {
             _gm17_generate_proof(
                 pk_path_cstring.as_ptr(),
                 proof_path_cstring.as_ptr(),
@@ -104,184 +131,137 @@ impl ProofSystem for GM17 {
                 private_inputs_arr[0].as_ptr(),
                 private_inputs_length as i32,
             )
+        };
+
+        if !done {
+            return Err(Error::Backend(String::from(
+                "libsnark proof generation failed",
+            )));
         }
-    }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>, is_abiv2: bool) -> String {
-        let mut lines = reader.lines();
+        let proof: Proof<ProofPoints> = Proof::read(File::open(&proof_path)?)?;
 
-        let (mut template_text, solidity_pairing_lib) = if is_abiv2 {
-            (
-                String::from(CONTRACT_TEMPLATE_V2),
-                String::from(SOLIDITY_PAIRING_LIB_V2),
-            )
-        } else {
-            (
-                String::from(CONTRACT_TEMPLATE),
-                String::from(SOLIDITY_PAIRING_LIB),
-            )
+        Ok(proof.to_json())
+    }
+
+    fn verify(
+        &self,
+        vk: &str,
+        proof: &str,
+        inputs: Option<Vec<FieldPrime>>,
+    ) -> Result<bool, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
+        let h = vk.h.to_bellman().map_err(Error::MalformedKey)?;
+        let g_alpha = vk.g_alpha.to_bellman().map_err(Error::MalformedKey)?;
+        let h_beta = vk.h_beta.to_bellman().map_err(Error::MalformedKey)?;
+        let g_gamma = vk.g_gamma.to_bellman().map_err(Error::MalformedKey)?;
+        let h_gamma = vk.h_gamma.to_bellman().map_err(Error::MalformedKey)?;
+        let query = vk
+            .query
+            .iter()
+            .map(|p| p.to_bellman().map_err(Error::MalformedKey))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let proof: Proof<ProofPoints> = Proof::read(proof.as_bytes())?;
+        let a = proof.proof.a.to_bellman().map_err(Error::MalformedProof)?;
+        let b = proof.proof.b.to_bellman().map_err(Error::MalformedProof)?;
+        let c = proof.proof.c.to_bellman().map_err(Error::MalformedProof)?;
+
+        // expected inputs take precedence over the ones embedded in the proof
+        let inputs: Vec<Fr> = match inputs {
+            Some(inputs) => inputs.into_iter().map(|i| i.into_bellman()).collect(),
+            None => proof.inputs_to_bellman()?,
         };
 
-        let query_template = String::from("vk.query[index] = Pairing.G1Point(points);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_query_len_regex = Regex::new(r#"(<%vk_query_length%>)"#).unwrap();
-        let vk_query_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_query_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_query_repeat_regex = Regex::new(r#"(<%vk_query_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
-
-        for _ in 0..5 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
+        if inputs.len() + 1 != query.len() {
+            return Ok(false);
         }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let query_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_query_len_regex
-            .replace(template_text.as_str(), format!("{}", query_count).as_str())
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", query_count - 1).as_str(),
-            )
-            .into_owned();
-
-        let mut query_repeat_text = String::new();
-        for x in 0..query_count {
-            let mut curr_template = query_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_query_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_query_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            query_repeat_text.push_str(curr_template.as_str());
-            if x < query_count - 1 {
-                query_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_query_repeat_regex
-            .replace(template_text.as_str(), query_repeat_text.as_str())
-            .into_owned();
+        let vk_x = accumulate_inputs(&query, &inputs);
+
+        // e(A*G^{alpha}, B*H^{beta}) = e(G^{alpha}, H^{beta}) * e(G^{psi}, H^{gamma}) * e(C, H)
+        Ok(pairing_product_is_one(&[
+            (g_alpha, h_beta),
+            (vk_x, h_gamma),
+            (c, h),
+            (g1_neg(&g1_add(&a, &g_alpha)), g2_add(&b, &h_beta)),
+        ])
+        // e(A, H^{gamma}) = e(G^{gamma}, B)
+        && pairing_product_is_one(&[(a, h_gamma), (g1_neg(&g_gamma), b)]))
+    }
 
-        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+    fn export_solidity_verifier(&self, vk: &str, is_abiv2: bool) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
 
-        format!(
+        let (template, solidity_pairing_lib) = if is_abiv2 {
+            (CONTRACT_TEMPLATE_V2, SOLIDITY_PAIRING_LIB_V2)
+        } else {
+            (CONTRACT_TEMPLATE, SOLIDITY_PAIRING_LIB)
+        };
+
+        let query_pts = vk
+            .query
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("vk.query[{}] = Pairing.G1Point({});", i, solidity_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let template_text = template
+            .replace("<%vk_h%>", &solidity_g2(&vk.h))
+            .replace("<%vk_g_alpha%>", &solidity_g1(&vk.g_alpha))
+            .replace("<%vk_h_beta%>", &solidity_g2(&vk.h_beta))
+            .replace("<%vk_g_gamma%>", &solidity_g1(&vk.g_gamma))
+            .replace("<%vk_h_gamma%>", &solidity_g2(&vk.h_gamma))
+            .replace("<%vk_query_length%>", &vk.query.len().to_string())
+            .replace("<%vk_query_pts%>", &query_pts)
+            .replace("<%vk_input_length%>", &(vk.query.len() - 1).to_string());
+
+        Ok(format!(
             "{}{}{}",
             SOLIDITY_G2_ADDITION_LIB, solidity_pairing_lib, template_text
-        )
+        ))
     }
 
-    fn export_avm_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
-        let mut template_text = String::from(CONTRACT_AVM_TEMPLATE);
-
-        let query_template = String::from("query[index] = new G1Point(coord, coord);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_query_len_regex = Regex::new(r#"(<%vk_query_length%>)"#).unwrap();
-        let vk_query_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_query_points_regex = Regex::new(r#"coord"#).unwrap();
-        let vk_query_repeat_regex = Regex::new(r#"(<%vk_query_pts%>)"#).unwrap();
-
-        let vk_value = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-
-        for _ in 0..5 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-
-            let mut values = Vec::new();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                values.push(value.as_str());
-            }
-
-            if values.len() == 4 {
-                template_text = vk_regex.replace(template_text.as_str(), values[1])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[0])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[3])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[2])
-                    .into_owned();
-            } else if values.len() == 2 {
-                template_text = vk_regex.replace(template_text.as_str(), values[0])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[1])
-                    .into_owned();
-            }
-        }
+    fn export_avm_verifier(
+        &self,
+        vk: &str,
+        names: &JavaNames,
+        is_callable: bool,
+    ) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
+
+        let query_pts = vk
+            .query
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("query[{}] = new G1Point({});", i, java_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let verifier = CONTRACT_AVM_TEMPLATE
+            .replace("<%vk_h%>", &java_g2(&vk.h))
+            .replace("<%vk_g_alpha%>", &java_g1(&vk.g_alpha))
+            .replace("<%vk_h_beta%>", &java_g2(&vk.h_beta))
+            .replace("<%vk_g_gamma%>", &java_g1(&vk.g_gamma))
+            .replace("<%vk_h_gamma%>", &java_g2(&vk.h_gamma))
+            .replace("<%vk_query_length%>", &vk.query.len().to_string())
+            .replace("<%vk_query_pts%>", &query_pts);
+
+        Ok(names.apply(&java_entry_point(&verifier, is_callable)))
+    }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let query_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_query_len_regex
-            .replace(template_text.as_str(), format!("{}", query_count).as_str())
-            .into_owned();
-
-        let mut query_repeat_text = String::new();
-        for x in 0..query_count {
-            let mut curr_template = query_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_query_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                curr_template = vk_query_points_regex
-                    .replace(curr_template.as_str(), value.as_str())
-                    .into_owned();
-            }
-
-            query_repeat_text.push_str(curr_template.as_str());
-            if x < query_count - 1 {
-                query_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_query_repeat_regex
-            .replace(template_text.as_str(), query_repeat_text.as_str())
-            .into_owned();
+    fn export_avm_proof(&self, proof: &str) -> Result<JavaProof, Error> {
+        let proof: Proof<ProofPoints> = Proof::read(proof.as_bytes())?;
 
-        let re = Regex::new(r"0[xX](?P<v>[0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "\"$v\"").to_string();
-        format!("{}", template_text)
+        JavaProof::new(
+            &[
+                java_g1_bytes(&proof.proof.a)?,
+                java_g2_bytes(&proof.proof.b)?,
+                java_g1_bytes(&proof.proof.c)?,
+            ],
+            &proof.inputs,
+        )
     }
 }
 
@@ -426,7 +406,7 @@ contract Verifier {
 "#;
 
 const CONTRACT_AVM_TEMPLATE: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import avm.Blockchain;
 import org.aion.avm.tooling.abi.Callable;
@@ -435,7 +415,7 @@ import java.math.BigInteger;
 import java.util.Arrays;
 
 @SuppressWarnings({"WeakerAccess", "unused"})
-public class Verifier {
+public class <%class%> {
 
     protected static class VerifyingKey {
 
@@ -494,23 +474,11 @@ public class Verifier {
     }
 
     protected static VerifyingKey verifyingKey() {
-        G2Point h = new G2Point(<%vk_hxx%>, <%vk_hxy%>, <%vk_hyx%>, <%vk_hyy%>);
-
-        G1Point g_alpha = new G1Point(<%vk_gax%>,
-                                      <%vk_gay%>);
-
-        G2Point h_beta = new G2Point(<%vk_hbxx%>,
-                                    <%vk_hbxy%>,
-                                    <%vk_hbyx%>,
-                                    <%vk_hbyy%>);
-
-        G1Point g_gamma = new G1Point(<%vk_ggx%>,
-                                     <%vk_ggy%>);
-
-        G2Point h_gamma = new G2Point(<%vk_hgxx%>,
-                                    <%vk_hgxy%>,
-                                    <%vk_hgyx%>,
-                                    <%vk_hgyy%>);
+        G2Point h = new G2Point(<%vk_h%>);
+        G1Point g_alpha = new G1Point(<%vk_g_alpha%>);
+        G2Point h_beta = new G2Point(<%vk_h_beta%>);
+        G1Point g_gamma = new G1Point(<%vk_g_gamma%>);
+        G2Point h_gamma = new G2Point(<%vk_h_gamma%>);
 
         G1Point[] query = new G1Point[<%vk_query_length%>];
         <%vk_query_pts%>
@@ -540,22 +508,5 @@ public class Verifier {
 
         return Pairing.pairingProd2(proof.a, vk.h_gamma, G1.negate(vk.g_gamma), proof.b);
     }
-
-    @Callable
-    public static boolean verify(BigInteger[] input, byte[] proof) {
-        Blockchain.println("verify() called");
-
-        try {
-            if (verify(input, Proof.deserialize(proof))) {
-                Blockchain.log("VerifySnark".getBytes(), BigInteger.ONE.toByteArray());
-                return true;
-            }
-        } catch (Exception e) {
-            Blockchain.println("verify() failed with exception: " + e.getMessage());
-        }
-
-        Blockchain.log("VerifySnark".getBytes(), BigInteger.ZERO.toByteArray());
-        return false;
-    }
-}
+<%entry_point%>}
 "#;
@@ -4,10 +4,41 @@ mod gm17;
 #[cfg(feature = "libsnark")]
 mod pghr13;
 
+pub mod mpc;
+pub mod phase1;
+pub mod types;
 pub mod utils;
 
-pub use self::g16::G16;
+pub use self::g16::{G16ProvingKey, G16};
 #[cfg(feature = "libsnark")]
 pub use self::gm17::GM17;
 #[cfg(feature = "libsnark")]
 pub use self::pghr13::PGHR13;
+
+#[cfg(test)]
+mod tests {
+    use crate::flat_absy::FlatVariable;
+    use crate::ir::{Function, Prog, Statement};
+    use typed_absy::types::{Signature, Type};
+    use zokrates_field::field::FieldPrime;
+
+    /// A program with one public input and a single constraint, which returns its input
+    pub fn program() -> Prog<FieldPrime> {
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                arguments: vec![FlatVariable::new(0)],
+                returns: vec![FlatVariable::public(0)],
+                statements: vec![Statement::Constraint(
+                    FlatVariable::new(0).into(),
+                    FlatVariable::public(0).into(),
+                )],
+                debug: None,
+            },
+            private: vec![false],
+            signature: Signature::new()
+                .inputs(vec![Type::FieldElement])
+                .outputs(vec![Type::FieldElement]),
+        }
+    }
+}
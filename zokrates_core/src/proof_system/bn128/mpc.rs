@@ -0,0 +1,490 @@
+//! Multi-party computation for the circuit-specific (phase 2) part of the Groth16 setup.
+//!
+//! The ceremony starts from parameters in which `delta` is one. Each participant multiplies
+//! `delta` by a secret random scalar, divides the `h` and `l` queries by it, and publishes a
+//! public key proving knowledge of that scalar. The resulting parameters are sound as long as
+//! at least one participant destroyed their secret.
+//!
+//! Every contribution is identified by the BLAKE2b hash of its public key, and the public keys
+//! are chained through a transcript hash, so the full history of a ceremony can be checked
+//! offline with `MPCParameters::verify`.
+//!
+//! The circuit-independent parameters (`alpha`, `beta` and the powers of `tau`) are taken from
+//! a Powers of Tau ceremony with `from_phase1`. `new` samples them locally instead, which means
+//! whoever initializes the ceremony must be trusted to discard them.
+
+extern crate rand;
+
+use self::rand::{ChaChaRng, OsRng, Rand, Rng, SeedableRng};
+use crate::ir;
+use crate::proof_system::bn128::g16::serialize::serialize_vk;
+use crate::proof_system::bn128::phase1::{merge_pairs, read_point, same_ratio, Phase1};
+use crate::proof_system::bn128::utils::bellman::Computation;
+use crate::proof_system::{Error, SetupKeypair};
+use bellman::groth16::{generate_parameters, Parameters};
+use bincode::{serialize, Infinite};
+use blake2_rfc::blake2b::{blake2b, Blake2b};
+use pairing::bn256::{Bn256, Fr, G1Affine, G2Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, Field, PrimeField};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+const LOCAL_PHASE1_WARNING: &str = "WARNING: The circuit-independent parameters are sampled locally instead of being taken from a Powers of Tau ceremony. Whoever ran this initialization can forge proofs unless they discarded them, no matter how many participants contribute afterwards. Do not use these parameters in production.";
+use zokrates_field::field::FieldPrime;
+
+/// The state of a phase 2 ceremony: the current Groth16 parameters, a hash binding them to the
+/// program and to the initial parameters, and the public keys of all contributions so far
+pub struct MPCParameters {
+    params: Parameters<Bn256>,
+    cs_hash: [u8; 64],
+    contributions: Vec<PublicKey>,
+}
+
+/// The public part of a contribution. `s_delta` and `r_delta` prove knowledge of the secret
+/// `delta`, where `r` is derived from the transcript so that contributions cannot be replayed
+struct PublicKey {
+    delta_after: G1Affine,
+    s: G1Affine,
+    s_delta: G1Affine,
+    r_delta: G2Affine,
+    transcript: [u8; 64],
+}
+
+impl MPCParameters {
+    /// Initializes a ceremony for `program`, with `delta` set to one and the circuit-independent
+    /// parameters sampled locally. This is only suitable for testing, as the ceremony is then
+    /// only as sound as the machine running it
+    pub fn new(program: ir::Prog<FieldPrime>) -> Result<Self, Error> {
+        let program_bytes =
+            serialize(&program, Infinite).map_err(|e| Error::Backend(e.to_string()))?;
+
+        println!("{}", LOCAL_PHASE1_WARNING);
+
+        let rng = &mut OsRng::new().map_err(|e| Error::Backend(e.to_string()))?;
+
+        let params = generate_parameters::<Bn256, _>(
+            Computation::without_witness(&program),
+            G1::one(),
+            G2::one(),
+            Fr::rand(rng),
+            Fr::rand(rng),
+            Fr::one(),
+            Fr::one(),
+            Fr::rand(rng),
+        )?;
+
+        Self::initial(&program_bytes, params)
+    }
+
+    /// Initializes a ceremony for `program`, with `delta` set to one and the circuit-independent
+    /// parameters derived from `phase1`. This is deterministic, so the initial parameters can
+    /// be recomputed by anyone holding the same phase 1 file
+    pub fn from_phase1(program: ir::Prog<FieldPrime>, phase1: &Phase1) -> Result<Self, Error> {
+        let program_bytes =
+            serialize(&program, Infinite).map_err(|e| Error::Backend(e.to_string()))?;
+
+        let params = phase1.parameters(program)?;
+
+        Self::initial(&program_bytes, params)
+    }
+
+    fn initial(program_bytes: &[u8], params: Parameters<Bn256>) -> Result<Self, Error> {
+        Ok(MPCParameters {
+            cs_hash: cs_hash(program_bytes, &params)?,
+            params,
+            contributions: vec![],
+        })
+    }
+
+    /// Reads parameters written by `write`. If `checked` is set, all points are checked to be
+    /// on the curve and in the right subgroup, which is slow for large circuits
+    pub fn read<R: Read>(reader: R, checked: bool) -> Result<Self, Error> {
+        Self::read_inner(reader, checked).map_err(|e| Error::MalformedKey(e.to_string()))
+    }
+
+    fn read_inner<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
+        let params = Parameters::read(&mut reader, checked)?;
+
+        let mut cs_hash = [0u8; 64];
+        reader.read_exact(&mut cs_hash)?;
+
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+
+        let mut contributions = vec![];
+        for _ in 0..u32::from_be_bytes(len) {
+            contributions.push(PublicKey::read(&mut reader)?);
+        }
+
+        Ok(MPCParameters {
+            params,
+            cs_hash,
+            contributions,
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        self.params.write(&mut writer)?;
+        writer.write_all(&self.cs_hash)?;
+        writer.write_all(&(self.contributions.len() as u32).to_be_bytes())?;
+        for pubkey in &self.contributions {
+            pubkey.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a contribution using randomness from the operating system mixed with `entropy`,
+    /// and returns its hash
+    pub fn contribute(&mut self, entropy: &[u8]) -> Result<[u8; 64], Error> {
+        let mut seed = [0u8; 32];
+        OsRng::new()
+            .map_err(|e| Error::Backend(e.to_string()))?
+            .fill_bytes(&mut seed);
+
+        let mut sink = HashWriter::new();
+        sink.write_all(&seed)?;
+        sink.write_all(entropy)?;
+
+        Ok(self.contribute_with(&mut seeded_rng(&sink.into_hash())))
+    }
+
+    /// Adds a contribution derived from a public random `beacon`, hashed `2^exponent` times,
+    /// and returns its hash. Applying the same beacon to the same parameters always yields the
+    /// same result, so anyone can reproduce this contribution
+    pub fn apply_beacon(&mut self, beacon: &[u8], exponent: u8) -> [u8; 64] {
+        assert!(exponent < 64, "beacon exponent must be smaller than 64");
+
+        let mut digest = beacon.to_vec();
+        for _ in 0..(1u64 << exponent) {
+            digest = blake2b(64, &[], &digest).as_bytes().to_vec();
+        }
+
+        self.contribute_with(&mut seeded_rng(&digest))
+    }
+
+    fn contribute_with<R: Rng>(&mut self, rng: &mut R) -> [u8; 64] {
+        let delta = loop {
+            let delta = Fr::rand(rng);
+            if !delta.is_zero() {
+                break delta;
+            }
+        };
+        let delta_inv = delta.inverse().unwrap();
+
+        let s = G1::rand(rng).into_affine();
+        let s_delta = s.mul(delta.into_repr()).into_affine();
+        let transcript = self.transcript(&s, &s_delta);
+        let r_delta = hash_to_g2(&transcript).mul(delta.into_repr()).into_affine();
+
+        let vk = &mut self.params.vk;
+        vk.delta_g1 = vk.delta_g1.mul(delta.into_repr()).into_affine();
+        vk.delta_g2 = vk.delta_g2.mul(delta.into_repr()).into_affine();
+        self.params.h = Arc::new(batch_mul(&self.params.h, delta_inv));
+        self.params.l = Arc::new(batch_mul(&self.params.l, delta_inv));
+
+        let pubkey = PublicKey {
+            delta_after: self.params.vk.delta_g1,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        };
+        let hash = pubkey.hash();
+        self.contributions.push(pubkey);
+
+        hash
+    }
+
+    fn transcript(&self, s: &G1Affine, s_delta: &G1Affine) -> [u8; 64] {
+        let mut sink = HashWriter::new();
+        sink.write_all(&self.cs_hash).unwrap();
+        for pubkey in &self.contributions {
+            pubkey.write(&mut sink).unwrap();
+        }
+        sink.write_all(s.into_uncompressed().as_ref()).unwrap();
+        sink.write_all(s_delta.into_uncompressed().as_ref())
+            .unwrap();
+        sink.into_hash()
+    }
+
+    /// Checks that these parameters were obtained from `initial`, the parameters produced by
+    /// `new` for `program`, through a sequence of valid contributions, and returns the hashes
+    /// of those contributions in order
+    pub fn verify(
+        &self,
+        program: &ir::Prog<FieldPrime>,
+        initial: &MPCParameters,
+    ) -> Result<Vec<[u8; 64]>, Error> {
+        let program_bytes =
+            serialize(program, Infinite).map_err(|e| Error::Backend(e.to_string()))?;
+        let expected_hash = cs_hash(&program_bytes, &initial.params)?;
+
+        if initial.cs_hash[..] != expected_hash[..] || self.cs_hash[..] != expected_hash[..] {
+            return Err(invalid("parameters do not belong to this program"));
+        }
+
+        if !initial.contributions.is_empty()
+            || initial.params.vk.delta_g1 != G1Affine::one()
+            || initial.params.vk.delta_g2 != G2Affine::one()
+        {
+            return Err(invalid("initial parameters already contain contributions"));
+        }
+
+        let (before, after) = (&initial.params, &self.params);
+
+        if before.vk.alpha_g1 != after.vk.alpha_g1
+            || before.vk.beta_g1 != after.vk.beta_g1
+            || before.vk.beta_g2 != after.vk.beta_g2
+            || before.vk.gamma_g2 != after.vk.gamma_g2
+            || before.vk.ic != after.vk.ic
+            || before.a != after.a
+            || before.b_g1 != after.b_g1
+            || before.b_g2 != after.b_g2
+            || before.h.len() != after.h.len()
+            || before.l.len() != after.l.len()
+        {
+            return Err(invalid("parameters were modified outside of delta"));
+        }
+
+        let mut current_delta = G1Affine::one();
+        let mut hashes = vec![];
+
+        for (i, pubkey) in self.contributions.iter().enumerate() {
+            let mut sink = HashWriter::new();
+            sink.write_all(&self.cs_hash)?;
+            for previous in &self.contributions[..i] {
+                previous.write(&mut sink)?;
+            }
+            sink.write_all(pubkey.s.into_uncompressed().as_ref())?;
+            sink.write_all(pubkey.s_delta.into_uncompressed().as_ref())?;
+
+            if sink.into_hash()[..] != pubkey.transcript[..] {
+                return Err(invalid(&format!(
+                    "contribution {} has a wrong transcript",
+                    i + 1
+                )));
+            }
+
+            let r = hash_to_g2(&pubkey.transcript);
+
+            if pubkey.s.is_zero()
+                || !same_ratio((pubkey.s, pubkey.s_delta), (r, pubkey.r_delta))
+                || !same_ratio((current_delta, pubkey.delta_after), (r, pubkey.r_delta))
+            {
+                return Err(invalid(&format!(
+                    "contribution {} does not prove knowledge of its secret",
+                    i + 1
+                )));
+            }
+
+            current_delta = pubkey.delta_after;
+            hashes.push(pubkey.hash());
+        }
+
+        if after.vk.delta_g1 != current_delta
+            || !same_ratio(
+                (G1Affine::one(), after.vk.delta_g1),
+                (G2Affine::one(), after.vk.delta_g2),
+            )
+        {
+            return Err(invalid("delta does not match the last contribution"));
+        }
+
+        if !same_ratio(
+            merge_pairs(&before.h, &after.h),
+            (after.vk.delta_g2, G2Affine::one()),
+        ) || !same_ratio(
+            merge_pairs(&before.l, &after.l),
+            (after.vk.delta_g2, G2Affine::one()),
+        ) {
+            return Err(invalid("h or l query is not consistent with delta"));
+        }
+
+        Ok(hashes)
+    }
+
+    /// Returns the Groth16 keypair defined by the current parameters
+    pub fn keypair(&self) -> Result<SetupKeypair, Error> {
+        let mut pk = vec![];
+        self.params.write(&mut pk)?;
+
+        Ok(SetupKeypair {
+            vk: serialize_vk(&self.params.vk).to_json(),
+            pk,
+        })
+    }
+}
+
+impl PublicKey {
+    fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let delta_after = read_point(&mut reader)?;
+        let s = read_point(&mut reader)?;
+        let s_delta = read_point(&mut reader)?;
+        let r_delta = read_point(&mut reader)?;
+
+        let mut transcript = [0u8; 64];
+        reader.read_exact(&mut transcript)?;
+
+        Ok(PublicKey {
+            delta_after,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        })
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.delta_after.into_uncompressed().as_ref())?;
+        writer.write_all(self.s.into_uncompressed().as_ref())?;
+        writer.write_all(self.s_delta.into_uncompressed().as_ref())?;
+        writer.write_all(self.r_delta.into_uncompressed().as_ref())?;
+        writer.write_all(&self.transcript)
+    }
+
+    fn hash(&self) -> [u8; 64] {
+        let mut sink = HashWriter::new();
+        self.write(&mut sink).unwrap();
+        sink.into_hash()
+    }
+}
+
+/// A writer which hashes everything written to it with BLAKE2b
+struct HashWriter(Blake2b);
+
+impl HashWriter {
+    fn new() -> Self {
+        HashWriter(Blake2b::new(64))
+    }
+
+    fn into_hash(self) -> [u8; 64] {
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(self.0.finalize().as_bytes());
+        hash
+    }
+}
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidCeremony(String::from(message))
+}
+
+fn cs_hash(program_bytes: &[u8], params: &Parameters<Bn256>) -> Result<[u8; 64], Error> {
+    let mut sink = HashWriter::new();
+    sink.write_all(program_bytes)?;
+    params.write(&mut sink)?;
+    Ok(sink.into_hash())
+}
+
+fn seeded_rng(digest: &[u8]) -> ChaChaRng {
+    let seed: Vec<u32> = digest[..32]
+        .chunks(4)
+        .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+    ChaChaRng::from_seed(&seed[..])
+}
+
+fn hash_to_g2(digest: &[u8]) -> G2Affine {
+    G2::rand(&mut seeded_rng(digest)).into_affine()
+}
+
+fn batch_mul(points: &[G1Affine], scalar: Fr) -> Vec<G1Affine> {
+    let scalar = scalar.into_repr();
+    let mut projective: Vec<G1> = points.iter().map(|p| p.mul(scalar)).collect();
+    G1::batch_normalization(&mut projective);
+    projective.iter().map(|p| p.into_affine()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof_system::bn128::tests::program;
+    use crate::proof_system::{ProofSystem, G16};
+
+    fn round_trip(params: &MPCParameters) -> MPCParameters {
+        let mut buffer = vec![];
+        params.write(&mut buffer).unwrap();
+        MPCParameters::read(&buffer[..], true).unwrap()
+    }
+
+    #[test]
+    fn ceremony() {
+        let initial = MPCParameters::new(program()).unwrap();
+
+        let mut params = round_trip(&initial);
+        let first = params.contribute(b"first").unwrap();
+        let mut params = round_trip(&params);
+        let second = params.contribute(b"second").unwrap();
+        let beacon = params.apply_beacon(b"beacon", 2);
+
+        let hashes = params.verify(&program(), &initial).unwrap();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(&hashes[0][..], &first[..]);
+        assert_eq!(&hashes[1][..], &second[..]);
+        assert_eq!(&hashes[2][..], &beacon[..]);
+
+        let keypair = params.keypair().unwrap();
+        let witness = program().execute(&vec![FieldPrime::from(42)]).unwrap();
+        let proof = G16 {}
+            .generate_proof(program(), witness, &keypair.pk)
+            .unwrap();
+        assert!(G16 {}.verify(&keypair.vk, &proof, None).unwrap());
+    }
+
+    #[test]
+    fn beacon_is_deterministic() {
+        let initial = MPCParameters::new(program()).unwrap();
+
+        let mut a = round_trip(&initial);
+        let mut b = round_trip(&initial);
+
+        assert_eq!(
+            &a.apply_beacon(b"beacon", 1)[..],
+            &b.apply_beacon(b"beacon", 1)[..]
+        );
+    }
+
+    #[test]
+    fn tampered_delta() {
+        let initial = MPCParameters::new(program()).unwrap();
+
+        let mut params = round_trip(&initial);
+        params.contribute(b"honest").unwrap();
+
+        // replace delta without a matching contribution
+        params.params.vk.delta_g1 = G1Affine::one()
+            .mul(Fr::from_str("7").unwrap())
+            .into_affine();
+
+        match params.verify(&program(), &initial) {
+            Err(Error::InvalidCeremony(_)) => {}
+            r => panic!("expected an invalid ceremony, got {:?}", r.map(|h| h.len())),
+        }
+    }
+
+    #[test]
+    fn other_program() {
+        let initial = MPCParameters::new(program()).unwrap();
+        let mut params = round_trip(&initial);
+        params.contribute(b"").unwrap();
+
+        let mut other = program();
+        other.private = vec![true];
+
+        match params.verify(&other, &initial) {
+            Err(Error::InvalidCeremony(_)) => {}
+            r => panic!("expected an invalid ceremony, got {:?}", r.map(|h| h.len())),
+        }
+    }
+}
@@ -2,17 +2,26 @@ extern crate libc;
 
 use self::libc::{c_char, c_int};
 use ir;
-use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup};
+use pairing::bn256::{Fr, G2Affine};
+use pairing::CurveAffine;
+use proof_system::bn128::types::pghr13::{ProofPoints, VerificationKey};
+use proof_system::bn128::types::Proof;
+use proof_system::bn128::utils::bellman::{
+    accumulate_inputs, g1_add, g1_neg, pairing_product_is_one,
+};
+use proof_system::bn128::utils::java::{
+    java_entry_point, java_g1, java_g1_bytes, java_g2, java_g2_bytes, JavaNames, JavaProof,
+};
+use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup, ScratchDir};
 use proof_system::bn128::utils::solidity::{
-    SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, SOLIDITY_PAIRING_LIB_V2,
+    solidity_g1, solidity_g2, SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB,
+    SOLIDITY_PAIRING_LIB_V2,
 };
-use proof_system::ProofSystem;
+use proof_system::{Error, ProofSystem, SetupKeypair};
 
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs::{self, File};
 
-use zokrates_field::field::FieldPrime;
+use zokrates_field::field::{Field, FieldPrime};
 
 pub struct PGHR13 {}
 
@@ -48,7 +57,11 @@ extern "C" {
 }
 
 impl ProofSystem for PGHR13 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(&self, program: ir::Prog<FieldPrime>) -> Result<SetupKeypair, Error> {
+        let scratch = ScratchDir::new()?;
+        let pk_path = scratch.path("proving.key");
+        let vk_path = scratch.path("verification.key");
+
         let (
             a_arr,
             b_arr,
@@ -61,9 +74,9 @@ impl ProofSystem for PGHR13 {
             num_inputs,
             pk_path_cstring,
             vk_path_cstring,
-        ) = prepare_setup(program, pk_path, vk_path);
+        ) = prepare_setup(program, &pk_path, &vk_path)?;
 
-        unsafe {
+        let done = unsafe {
             _pghr13_setup(
                 a_arr.as_ptr(),
                 b_arr.as_ptr(),
@@ -76,17 +89,32 @@ impl ProofSystem for PGHR13 {
                 num_inputs as i32,
                 pk_path_cstring.as_ptr(),
                 vk_path_cstring.as_ptr(),
-            );
+            )
+        };
+
+        if !done {
+            return Err(Error::Backend(String::from("libsnark setup failed")));
         }
+
+        // libsnark writes the verification key in the legacy text format, which we convert to json
+        Ok(SetupKeypair {
+            vk: VerificationKey::read(File::open(&vk_path)?)?.to_json(),
+            pk: fs::read(&pk_path)?,
+        })
     }
 
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
         witness: ir::Witness<FieldPrime>,
-        pk_path: &str,
-        proof_path: &str,
-    ) -> bool {
+        pk: &[u8],
+    ) -> Result<String, Error> {
+        let scratch = ScratchDir::new()?;
+        let pk_path = scratch.path("proving.key");
+        let proof_path = scratch.path("proof.json");
+
+        fs::write(&pk_path, pk)?;
+
         let (
             pk_path_cstring,
             proof_path_cstring,
@@ -94,14 +122,9 @@ impl ProofSystem for PGHR13 {
             public_inputs_length,
             private_inputs_arr,
             private_inputs_length,
-        ) = prepare_generate_proof(program, witness, pk_path, proof_path);
+        ) = prepare_generate_proof(program, witness, &pk_path, &proof_path)?;
 
-        println!(
-            "{:?}",
-            (pk_path_cstring.clone(), proof_path_cstring.clone(),)
-        );
-
-        unsafe {
+        let done = unsafe {
             _pghr13_generate_proof(
                 pk_path_cstring.as_ptr(),
                 proof_path_cstring.as_ptr(),
@@ -110,181 +133,159 @@ impl ProofSystem for PGHR13 {
                 private_inputs_arr[0].as_ptr(),
                 private_inputs_length as i32,
             )
+        };
+
+        if !done {
+            return Err(Error::Backend(String::from(
+                "libsnark proof generation failed",
+            )));
         }
-    }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>, is_abiv2: bool) -> String {
-        let mut lines = reader.lines();
+        let proof: Proof<ProofPoints> = Proof::read(File::open(&proof_path)?)?;
 
-        let (mut template_text, solidity_pairing_lib) = if is_abiv2 {
-            (
-                String::from(CONTRACT_TEMPLATE_V2),
-                String::from(SOLIDITY_PAIRING_LIB_V2),
-            )
-        } else {
-            (
-                String::from(CONTRACT_TEMPLATE),
-                String::from(SOLIDITY_PAIRING_LIB),
-            )
+        Ok(proof.to_json())
+    }
+
+    fn verify(
+        &self,
+        vk: &str,
+        proof: &str,
+        inputs: Option<Vec<FieldPrime>>,
+    ) -> Result<bool, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
+        let vk_a = vk.a.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_b = vk.b.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_c = vk.c.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_gamma = vk.gamma.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_gamma_beta_1 = vk.gamma_beta_1.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_gamma_beta_2 = vk.gamma_beta_2.to_bellman().map_err(Error::MalformedKey)?;
+        let vk_z = vk.z.to_bellman().map_err(Error::MalformedKey)?;
+        let ic = vk
+            .ic
+            .iter()
+            .map(|p| p.to_bellman().map_err(Error::MalformedKey))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let proof: Proof<ProofPoints> = Proof::read(proof.as_bytes())?;
+        let p = &proof.proof;
+        let a = p.a.to_bellman().map_err(Error::MalformedProof)?;
+        let a_p = p.a_p.to_bellman().map_err(Error::MalformedProof)?;
+        let b = p.b.to_bellman().map_err(Error::MalformedProof)?;
+        let b_p = p.b_p.to_bellman().map_err(Error::MalformedProof)?;
+        let c = p.c.to_bellman().map_err(Error::MalformedProof)?;
+        let c_p = p.c_p.to_bellman().map_err(Error::MalformedProof)?;
+        let h = p.h.to_bellman().map_err(Error::MalformedProof)?;
+        let k = p.k.to_bellman().map_err(Error::MalformedProof)?;
+
+        // expected inputs take precedence over the ones embedded in the proof
+        let inputs: Vec<Fr> = match inputs {
+            Some(inputs) => inputs.into_iter().map(|i| i.into_bellman()).collect(),
+            None => proof.inputs_to_bellman()?,
         };
 
-        let ic_template = String::from("vk.ic[index] = Pairing.G1Point(points);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_ic_len_regex = Regex::new(r#"(<%vk_ic_length%>)"#).unwrap();
-        let vk_ic_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_ic_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_ic_repeat_regex = Regex::new(r#"(<%vk_ic_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
-
-        for _ in 0..7 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
+        if inputs.len() + 1 != ic.len() {
+            return Ok(false);
         }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let ic_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_ic_len_regex
-            .replace(template_text.as_str(), format!("{}", ic_count).as_str())
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(template_text.as_str(), format!("{}", ic_count - 1).as_str())
-            .into_owned();
-
-        let mut ic_repeat_text = String::new();
-        for x in 0..ic_count {
-            let mut curr_template = ic_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_ic_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_ic_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            ic_repeat_text.push_str(curr_template.as_str());
-            if x < ic_count - 1 {
-                ic_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_ic_repeat_regex
-            .replace(template_text.as_str(), ic_repeat_text.as_str())
-            .into_owned();
+        let vk_x = accumulate_inputs(&ic, &inputs);
+        let p2 = G2Affine::one();
+
+        Ok(pairing_product_is_one(&[(a, vk_a), (g1_neg(&a_p), p2)])
+            && pairing_product_is_one(&[(vk_b, b), (g1_neg(&b_p), p2)])
+            && pairing_product_is_one(&[(c, vk_c), (g1_neg(&c_p), p2)])
+            && pairing_product_is_one(&[
+                (k, vk_gamma),
+                (g1_neg(&g1_add(&vk_x, &g1_add(&a, &c))), vk_gamma_beta_2),
+                (g1_neg(&vk_gamma_beta_1), b),
+            ])
+            && pairing_product_is_one(&[
+                (g1_add(&vk_x, &a), b),
+                (g1_neg(&h), vk_z),
+                (g1_neg(&c), p2),
+            ]))
+    }
 
-        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+    fn export_solidity_verifier(&self, vk: &str, is_abiv2: bool) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
 
-        format!(
+        let (template, solidity_pairing_lib) = if is_abiv2 {
+            (CONTRACT_TEMPLATE_V2, SOLIDITY_PAIRING_LIB_V2)
+        } else {
+            (CONTRACT_TEMPLATE, SOLIDITY_PAIRING_LIB)
+        };
+
+        let ic_pts = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("vk.ic[{}] = Pairing.G1Point({});", i, solidity_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let template_text = template
+            .replace("<%vk_a%>", &solidity_g2(&vk.a))
+            .replace("<%vk_b%>", &solidity_g1(&vk.b))
+            .replace("<%vk_c%>", &solidity_g2(&vk.c))
+            .replace("<%vk_g%>", &solidity_g2(&vk.gamma))
+            .replace("<%vk_gb1%>", &solidity_g1(&vk.gamma_beta_1))
+            .replace("<%vk_gb2%>", &solidity_g2(&vk.gamma_beta_2))
+            .replace("<%vk_z%>", &solidity_g2(&vk.z))
+            .replace("<%vk_ic_length%>", &vk.ic.len().to_string())
+            .replace("<%vk_ic_pts%>", &ic_pts)
+            .replace("<%vk_input_length%>", &(vk.ic.len() - 1).to_string());
+
+        Ok(format!(
             "{}{}{}",
             SOLIDITY_G2_ADDITION_LIB, solidity_pairing_lib, template_text
-        )
+        ))
     }
 
-    fn export_avm_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
-        let mut template_text = String::from(CONTRACT_AVM_TEMPLATE);
-
-        let ic_template = String::from("ic[index] = new G1Point(coord, coord);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_ic_len_regex = Regex::new(r#"(<%vk_ic_length%>)"#).unwrap();
-        let vk_ic_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_ic_points_regex = Regex::new(r#"coord"#).unwrap();
-        let vk_ic_repeat_regex = Regex::new(r#"(<%vk_ic_pts%>)"#).unwrap();
-
-        let vk_value = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-
-        for _ in 0..7 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-
-            let mut values = Vec::new();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                values.push(value.as_str());
-            }
-
-            if values.len() == 4 {
-                template_text = vk_regex.replace(template_text.as_str(), values[1])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[0])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[3])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[2])
-                    .into_owned();
-            } else if values.len() == 2 {
-                template_text = vk_regex.replace(template_text.as_str(), values[0])
-                    .into_owned();
-                template_text = vk_regex.replace(template_text.as_str(), values[1])
-                    .into_owned();
-            }
-        }
-
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let ic_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_ic_len_regex
-            .replace(template_text.as_str(), format!("{}", ic_count).as_str())
-            .into_owned();
-
-        let mut ic_repeat_text = String::new();
-        for x in 0..ic_count {
-            let mut curr_template = ic_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_ic_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            for value in vk_value.find_iter(current_line_split[1]) {
-                curr_template = vk_ic_points_regex
-                    .replace(curr_template.as_str(), value.as_str())
-                    .into_owned();
-            }
-
-            ic_repeat_text.push_str(curr_template.as_str());
-            if x < ic_count - 1 {
-                ic_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_ic_repeat_regex
-            .replace(template_text.as_str(), ic_repeat_text.as_str())
-            .into_owned();
+    fn export_avm_verifier(
+        &self,
+        vk: &str,
+        names: &JavaNames,
+        is_callable: bool,
+    ) -> Result<String, Error> {
+        let vk = VerificationKey::read(vk.as_bytes())?;
+
+        let ic_pts = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("ic[{}] = new G1Point({});", i, java_g1(p)))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let verifier = CONTRACT_AVM_TEMPLATE
+            .replace("<%vk_a%>", &java_g2(&vk.a))
+            .replace("<%vk_b%>", &java_g1(&vk.b))
+            .replace("<%vk_c%>", &java_g2(&vk.c))
+            .replace("<%vk_gamma%>", &java_g2(&vk.gamma))
+            .replace("<%vk_gamma_beta_1%>", &java_g1(&vk.gamma_beta_1))
+            .replace("<%vk_gamma_beta_2%>", &java_g2(&vk.gamma_beta_2))
+            .replace("<%vk_z%>", &java_g2(&vk.z))
+            .replace("<%vk_ic_length%>", &vk.ic.len().to_string())
+            .replace("<%vk_ic_pts%>", &ic_pts);
+
+        Ok(names.apply(&java_entry_point(&verifier, is_callable)))
+    }
 
-        let re = Regex::new(r"0[xX](?P<v>[0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "\"$v\"").to_string();
-        format!("{}", template_text)
+    fn export_avm_proof(&self, proof: &str) -> Result<JavaProof, Error> {
+        let proof: Proof<ProofPoints> = Proof::read(proof.as_bytes())?;
+
+        JavaProof::new(
+            &[
+                java_g1_bytes(&proof.proof.a)?,
+                java_g1_bytes(&proof.proof.a_p)?,
+                java_g2_bytes(&proof.proof.b)?,
+                java_g1_bytes(&proof.proof.b_p)?,
+                java_g1_bytes(&proof.proof.c)?,
+                java_g1_bytes(&proof.proof.c_p)?,
+                java_g1_bytes(&proof.proof.k)?,
+                java_g1_bytes(&proof.proof.h)?,
+            ],
+            &proof.inputs,
+        )
     }
 }
 
@@ -461,7 +462,7 @@ const CONTRACT_TEMPLATE: &str = r#"contract Verifier {
 "#;
 
 const CONTRACT_AVM_TEMPLATE: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import avm.Blockchain;
 import org.aion.avm.tooling.abi.Callable;
@@ -470,7 +471,7 @@ import java.math.BigInteger;
 import java.util.Arrays;
 
 @SuppressWarnings({"WeakerAccess", "unused"})
-public class Verifier {
+public class <%class%> {
 
     protected static class VerifyingKey {
 
@@ -563,36 +564,13 @@ public class Verifier {
     }
 
     protected static VerifyingKey verifyingKey() {
-        G2Point a = new G2Point(<%vk_axx%>,
-                <%vk_axy%>,
-                <%vk_ayx%>,
-                <%vk_ayy%>);
-
-        G1Point b = new G1Point(<%vk_bx%>,
-                <%vk_by%>);
-
-        G2Point c = new G2Point(<%vk_cxx%>,
-                <%vk_cxy%>,
-                <%vk_cyx%>,
-                <%vk_cyy%>);
-
-        G2Point gamma = new G2Point(<%vk_gxx%>,
-                <%vk_gxy%>,
-                <%vk_gyx%>,
-                <%vk_gyy%>);
-
-        G1Point gamma_beta_1 = new G1Point(<%vk_gbax%>,
-                <%vk_gbay%>);
-
-        G2Point gamma_beta_2 = new G2Point(<%vk_gbbxx%>,
-                <%vk_gbbxy%>,
-                <%vk_gbbyx%>,
-                <%vk_gbbyy%>);
-
-        G2Point z = new G2Point(<%vk_zxx%>,
-                <%vk_zxy%>,
-                <%vk_zyx%>,
-                <%vk_zyy%>);
+        G2Point a = new G2Point(<%vk_a%>);
+        G1Point b = new G1Point(<%vk_b%>);
+        G2Point c = new G2Point(<%vk_c%>);
+        G2Point gamma = new G2Point(<%vk_gamma%>);
+        G1Point gamma_beta_1 = new G1Point(<%vk_gamma_beta_1%>);
+        G2Point gamma_beta_2 = new G2Point(<%vk_gamma_beta_2%>);
+        G2Point z = new G2Point(<%vk_z%>);
 
         G1Point[] ic = new G1Point[<%vk_ic_length%>];
         <%vk_ic_pts%>
@@ -638,22 +616,5 @@ public class Verifier {
                 G1.negate(proof.h), vk.z,
                 G1.negate(proof.c), G2.G2_P);
     }
-
-    @Callable
-    public static boolean verify(BigInteger[] input, byte[] proof) {
-        Blockchain.println("verify() called");
-
-        try {
-            if (verify(input, Proof.deserialize(proof))) {
-                Blockchain.log("VerifySnark".getBytes(), BigInteger.ONE.toByteArray());
-                return true;
-            }
-        } catch (Exception e) {
-            Blockchain.println("verify() failed with exception: " + e.getMessage());
-        }
-
-        Blockchain.log("VerifySnark".getBytes(), BigInteger.ZERO.toByteArray());
-        return false;
-    }
-}
+<%entry_point%>}
 "#;
@@ -0,0 +1,656 @@
+//! Universal (phase 1) Groth16 parameters from a Powers of Tau ceremony.
+//!
+//! The accumulator is read in the uncompressed format written by the BN254 `powersoftau` tools
+//! (their `challenge` files): a 64 byte hash, followed by `2n - 1` powers of tau in G1, `n` powers
+//! of tau in G2, `n` powers of tau multiplied by alpha in G1, `n` powers of tau multiplied by beta
+//! in G1, and beta in G2. `n` must be a power of two, and is derived from the size of the file.
+//! As these files are large, only the powers needed for a given program are read.
+
+extern crate rand;
+
+use self::rand::{thread_rng, Rand};
+use crate::ir;
+use crate::proof_system::Error;
+use bellman::groth16::{Parameters, VerifyingKey};
+use bellman::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use pairing::bn256::{Bn256, Fr, G1Affine, G2Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, EncodedPoint, Field, PrimeField};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use zokrates_field::field::FieldPrime;
+
+const HASH_SIZE: usize = 64;
+const G1_SIZE: usize = 64;
+const G2_SIZE: usize = 128;
+
+pub struct Phase1 {
+    tau_powers_g1: Vec<G1Affine>,
+    tau_powers_g2: Vec<G2Affine>,
+    alpha_tau_powers_g1: Vec<G1Affine>,
+    beta_tau_powers_g1: Vec<G1Affine>,
+    beta_g2: G2Affine,
+}
+
+impl Phase1 {
+    /// Reads and validates the first `domain_size` powers of a Powers of Tau accumulator, or all
+    /// of them if it has fewer. `domain_size` is rounded up to a power of two, and is typically
+    /// `Phase1::domain_size` of the program to run the setup for
+    pub fn read<R: Read + Seek>(mut reader: R, domain_size: usize) -> Result<Self, Error> {
+        let len = reader.seek(SeekFrom::End(0))?;
+
+        // the hash and beta in G2 come on top of 2n - 1 + n + n points in G1 and n points in G2
+        let overhead = (HASH_SIZE - G1_SIZE + G2_SIZE) as u64;
+        let per_power = (4 * G1_SIZE + G2_SIZE) as u64;
+
+        let size = match len.checked_sub(overhead) {
+            Some(len) if len % per_power == 0 && (len / per_power).is_power_of_two() => {
+                (len / per_power) as usize
+            }
+            _ => {
+                return Err(Error::InvalidPhase1(format!(
+                    "unexpected file size of {} bytes",
+                    len
+                )))
+            }
+        };
+
+        if size < 2 {
+            return Err(Error::InvalidPhase1(String::from(
+                "file must contain at least two powers of tau",
+            )));
+        }
+
+        let count = domain_size.next_power_of_two().max(2).min(size);
+
+        let phase1 = Self::parse(&mut reader, size, count)
+            .map_err(|e| Error::InvalidPhase1(e.to_string()))?;
+
+        phase1.validate()?;
+
+        Ok(phase1)
+    }
+
+    /// Reads the first `count` powers out of the `size` powers of each section
+    fn parse<R: Read + Seek>(reader: &mut R, size: usize, count: usize) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(HASH_SIZE as u64))?;
+
+        let tau_powers_g1 = read_points(reader, 2 * count - 1)?;
+        skip_points(reader, 2 * (size - count), G1_SIZE)?;
+        let tau_powers_g2 = read_points(reader, count)?;
+        skip_points(reader, size - count, G2_SIZE)?;
+        let alpha_tau_powers_g1 = read_points(reader, count)?;
+        skip_points(reader, size - count, G1_SIZE)?;
+        let beta_tau_powers_g1 = read_points(reader, count)?;
+        skip_points(reader, size - count, G1_SIZE)?;
+
+        Ok(Phase1 {
+            tau_powers_g1,
+            tau_powers_g2,
+            alpha_tau_powers_g1,
+            beta_tau_powers_g1,
+            beta_g2: read_point(reader)?,
+        })
+    }
+
+    /// Returns the number of powers of tau needed to run the setup for `program`: its number of
+    /// constraints, including one per public input, rounded up to a power of two
+    pub fn domain_size(program: &ir::Prog<FieldPrime>) -> Result<usize, Error> {
+        Ok(assembly(program)?.num_constraints.next_power_of_two())
+    }
+
+    /// Returns the largest number of constraints, including one per public input, which these
+    /// parameters support
+    pub fn size(&self) -> usize {
+        self.tau_powers_g2.len()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |message: &str| Err(Error::InvalidPhase1(String::from(message)));
+
+        if self.tau_powers_g1[0] != G1Affine::one() || self.tau_powers_g2[0] != G2Affine::one() {
+            return invalid("powers of tau do not start at the generators");
+        }
+
+        let tau_g1 = (self.tau_powers_g1[0], self.tau_powers_g1[1]);
+        let tau_g2 = (self.tau_powers_g2[0], self.tau_powers_g2[1]);
+
+        if !same_ratio(power_pairs(&self.tau_powers_g1), tau_g2) {
+            return invalid("powers of tau in G1 are not consistent");
+        }
+        if !same_ratio(power_pairs(&self.tau_powers_g2), tau_g1) {
+            return invalid("powers of tau in G2 are not consistent");
+        }
+        if !same_ratio(power_pairs(&self.alpha_tau_powers_g1), tau_g2) {
+            return invalid("powers of tau multiplied by alpha are not consistent");
+        }
+        if !same_ratio(power_pairs(&self.beta_tau_powers_g1), tau_g2) {
+            return invalid("powers of tau multiplied by beta are not consistent");
+        }
+        if !same_ratio(
+            (self.tau_powers_g1[0], self.beta_tau_powers_g1[0]),
+            (self.tau_powers_g2[0], self.beta_g2),
+        ) {
+            return invalid("beta in G1 and G2 do not match");
+        }
+
+        Ok(())
+    }
+
+    /// Derives the Groth16 parameters for `program`. `gamma` and `delta` are both one, so the
+    /// parameters are only safe to use after a phase 2 contribution
+    pub fn parameters(&self, program: ir::Prog<FieldPrime>) -> Result<Parameters<Bn256>, Error> {
+        let assembly = assembly(&program)?;
+
+        let domain_size = assembly.num_constraints.next_power_of_two();
+
+        if domain_size > self.size() {
+            return Err(Error::CircuitTooLarge {
+                constraints: assembly.num_constraints,
+                supported: self.size(),
+            });
+        }
+
+        let mut omega = Fr::root_of_unity();
+        for _ in domain_size.trailing_zeros()..Fr::S {
+            omega.square();
+        }
+
+        let coeffs_g1 = lagrange_coefficients(&self.tau_powers_g1[..domain_size], omega);
+        let coeffs_g2 = lagrange_coefficients(&self.tau_powers_g2[..domain_size], omega);
+        let alpha_coeffs_g1 =
+            lagrange_coefficients(&self.alpha_tau_powers_g1[..domain_size], omega);
+        let beta_coeffs_g1 = lagrange_coefficients(&self.beta_tau_powers_g1[..domain_size], omega);
+
+        // tau^i * t(tau) = tau^(i + domain_size) - tau^i
+        let mut h: Vec<G1> = (0..domain_size - 1)
+            .map(|i| {
+                let mut p = self.tau_powers_g1[i + domain_size].into_projective();
+                p.sub_assign(&self.tau_powers_g1[i].into_projective());
+                p
+            })
+            .collect();
+        G1::batch_normalization(&mut h);
+
+        let eval = |at: &[Vec<(Fr, usize)>], bt: &[Vec<(Fr, usize)>], ct: &[Vec<(Fr, usize)>]| {
+            let mut a = vec![];
+            let mut b_g1 = vec![];
+            let mut b_g2 = vec![];
+            let mut ext = vec![];
+
+            for ((at, bt), ct) in at.iter().zip(bt.iter()).zip(ct.iter()) {
+                a.push(evaluate(&coeffs_g1, at));
+                b_g1.push(evaluate(&coeffs_g1, bt));
+                b_g2.push(evaluate(&coeffs_g2, bt));
+
+                let mut e = evaluate(&beta_coeffs_g1, at);
+                e.add_assign(&evaluate(&alpha_coeffs_g1, bt));
+                e.add_assign(&evaluate(&coeffs_g1, ct));
+                ext.push(e);
+            }
+
+            (a, b_g1, b_g2, ext)
+        };
+
+        let (mut a, mut b_g1, mut b_g2, ic) = eval(
+            &assembly.at_inputs,
+            &assembly.bt_inputs,
+            &assembly.ct_inputs,
+        );
+        let (a_aux, b_g1_aux, b_g2_aux, l) =
+            eval(&assembly.at_aux, &assembly.bt_aux, &assembly.ct_aux);
+
+        a.extend(a_aux);
+        b_g1.extend(b_g1_aux);
+        b_g2.extend(b_g2_aux);
+
+        // like bellman, do not allow unconstrained variables, so that the L query is fully dense
+        if l.iter().any(|e| e.is_zero()) {
+            return Err(SynthesisError::UnconstrainedVariable.into());
+        }
+
+        let vk = VerifyingKey {
+            alpha_g1: self.alpha_tau_powers_g1[0],
+            beta_g1: self.beta_tau_powers_g1[0],
+            beta_g2: self.beta_g2,
+            gamma_g2: G2Affine::one(),
+            delta_g1: G1Affine::one(),
+            delta_g2: G2Affine::one(),
+            ic: ic.into_iter().map(|e| e.into_affine()).collect(),
+        };
+
+        // like bellman, filter points at infinity away from the A and B queries
+        Ok(Parameters {
+            vk,
+            h: Arc::new(h.into_iter().map(|e| e.into_affine()).collect()),
+            l: Arc::new(l.into_iter().map(|e| e.into_affine()).collect()),
+            a: Arc::new(
+                a.into_iter()
+                    .filter(|e| !e.is_zero())
+                    .map(|e| e.into_affine())
+                    .collect(),
+            ),
+            b_g1: Arc::new(
+                b_g1.into_iter()
+                    .filter(|e| !e.is_zero())
+                    .map(|e| e.into_affine())
+                    .collect(),
+            ),
+            b_g2: Arc::new(
+                b_g2.into_iter()
+                    .filter(|e| !e.is_zero())
+                    .map(|e| e.into_affine())
+                    .collect(),
+            ),
+        })
+    }
+}
+
+/// Synthesizes `program` into its QAP polynomials
+fn assembly(program: &ir::Prog<FieldPrime>) -> Result<KeypairAssembly, Error> {
+    let mut assembly = KeypairAssembly::default();
+
+    assembly.alloc_input(|| "", || Ok(Fr::one()))?;
+    program.synthesize(&mut assembly, None)?;
+
+    // like bellman, add a dummy constraint for each input so that the IC query is fully dense
+    for i in 0..assembly.num_inputs {
+        assembly.enforce(
+            || "",
+            |lc| lc + Variable::new_unchecked(Index::Input(i)),
+            |lc| lc,
+            |lc| lc,
+        );
+    }
+
+    Ok(assembly)
+}
+
+/// Collects the QAP polynomials of a circuit, in the same way as bellman's parameter generator
+#[derive(Default)]
+struct KeypairAssembly {
+    num_inputs: usize,
+    num_aux: usize,
+    num_constraints: usize,
+    at_inputs: Vec<Vec<(Fr, usize)>>,
+    bt_inputs: Vec<Vec<(Fr, usize)>>,
+    ct_inputs: Vec<Vec<(Fr, usize)>>,
+    at_aux: Vec<Vec<(Fr, usize)>>,
+    bt_aux: Vec<Vec<(Fr, usize)>>,
+    ct_aux: Vec<Vec<(Fr, usize)>>,
+}
+
+impl ConstraintSystem<Bn256> for KeypairAssembly {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.num_aux;
+        self.num_aux += 1;
+
+        self.at_aux.push(vec![]);
+        self.bt_aux.push(vec![]);
+        self.ct_aux.push(vec![]);
+
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.num_inputs;
+        self.num_inputs += 1;
+
+        self.at_inputs.push(vec![]);
+        self.bt_inputs.push(vec![]);
+        self.ct_inputs.push(vec![]);
+
+        Ok(Variable::new_unchecked(Index::Input(index)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Bn256>) -> LinearCombination<Bn256>,
+        LB: FnOnce(LinearCombination<Bn256>) -> LinearCombination<Bn256>,
+        LC: FnOnce(LinearCombination<Bn256>) -> LinearCombination<Bn256>,
+    {
+        fn eval(
+            l: LinearCombination<Bn256>,
+            inputs: &mut [Vec<(Fr, usize)>],
+            aux: &mut [Vec<(Fr, usize)>],
+            this_constraint: usize,
+        ) {
+            for (variable, coeff) in l.as_ref() {
+                match variable.get_unchecked() {
+                    Index::Input(id) => inputs[id].push((*coeff, this_constraint)),
+                    Index::Aux(id) => aux[id].push((*coeff, this_constraint)),
+                }
+            }
+        }
+
+        let row = self.num_constraints;
+
+        eval(
+            a(LinearCombination::zero()),
+            &mut self.at_inputs,
+            &mut self.at_aux,
+            row,
+        );
+        eval(
+            b(LinearCombination::zero()),
+            &mut self.bt_inputs,
+            &mut self.bt_aux,
+            row,
+        );
+        eval(
+            c(LinearCombination::zero()),
+            &mut self.ct_inputs,
+            &mut self.ct_aux,
+            row,
+        );
+
+        self.num_constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+/// Evaluates a polynomial given by its coefficients in the Lagrange basis
+fn evaluate<G: CurveAffine<Scalar = Fr>>(coeffs: &[G], p: &[(Fr, usize)]) -> G::Projective {
+    let mut acc = G::Projective::zero();
+    for (coeff, index) in p {
+        acc.add_assign(&coeffs[*index].mul(coeff.into_repr()));
+    }
+    acc
+}
+
+/// Converts powers of tau to the Lagrange basis of the domain generated by `omega`, using an
+/// inverse FFT
+fn lagrange_coefficients<G: CurveAffine<Scalar = Fr>>(powers: &[G], omega: Fr) -> Vec<G> {
+    let mut values: Vec<G::Projective> = powers.iter().map(|p| p.into_projective()).collect();
+
+    fft(&mut values, omega.inverse().unwrap());
+
+    let size_inv = Fr::from_str(&values.len().to_string())
+        .unwrap()
+        .inverse()
+        .unwrap();
+    for v in values.iter_mut() {
+        v.mul_assign(size_inv.into_repr());
+    }
+
+    G::Projective::batch_normalization(&mut values);
+    values.iter().map(|v| v.into_affine()).collect()
+}
+
+fn fft<G: CurveProjective<Scalar = Fr>>(a: &mut [G], omega: Fr) {
+    fn bitreverse(mut n: usize, l: u32) -> usize {
+        let mut r = 0;
+        for _ in 0..l {
+            r = (r << 1) | (n & 1);
+            n >>= 1;
+        }
+        r
+    }
+
+    let n = a.len();
+    let log_n = n.trailing_zeros();
+
+    for k in 0..n {
+        let rk = bitreverse(k, log_n);
+        if k < rk {
+            a.swap(rk, k);
+        }
+    }
+
+    let mut m = 1;
+    for _ in 0..log_n {
+        let w_m = omega.pow(&[(n / (2 * m)) as u64]);
+
+        let mut k = 0;
+        while k < n {
+            let mut w = Fr::one();
+            for j in 0..m {
+                let mut t = a[k + j + m];
+                t.mul_assign(w.into_repr());
+                let mut tmp = a[k + j];
+                tmp.sub_assign(&t);
+                a[k + j + m] = tmp;
+                a[k + j].add_assign(&t);
+                w.mul_assign(&w_m);
+            }
+
+            k += 2 * m;
+        }
+
+        m *= 2;
+    }
+}
+
+pub(super) fn read_point<G: CurveAffine, R: Read>(reader: &mut R) -> io::Result<G> {
+    let mut repr = G::Uncompressed::empty();
+    reader.read_exact(repr.as_mut())?;
+
+    let point = repr
+        .into_affine()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    if point.is_zero() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "point at infinity",
+        ));
+    }
+
+    Ok(point)
+}
+
+fn read_points<G: CurveAffine, R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<G>> {
+    (0..count).map(|_| read_point(reader)).collect()
+}
+
+fn skip_points<R: Seek>(reader: &mut R, count: usize, point_size: usize) -> io::Result<()> {
+    reader.seek(SeekFrom::Current((count * point_size) as i64))?;
+    Ok(())
+}
+
+/// Checks that `g1.1 / g1.0 == g2.1 / g2.0` in the exponent
+pub(super) fn same_ratio<G: CurveAffine>(g1: (G, G), g2: (G::Pair, G::Pair)) -> bool {
+    g1.0.pairing_with(&g2.1) == g1.1.pairing_with(&g2.0)
+}
+
+/// Combines two equally long vectors with the same random coefficients, so that checking the
+/// ratio of the results checks the ratio of all pairs of elements with high probability
+pub(super) fn merge_pairs<G: CurveAffine>(v1: &[G], v2: &[G]) -> (G, G) {
+    let rng = &mut thread_rng();
+
+    let mut s = G::Projective::zero();
+    let mut sx = G::Projective::zero();
+
+    for (a, b) in v1.iter().zip(v2.iter()) {
+        let rho = G::Scalar::rand(rng).into_repr();
+        s.add_assign(&a.mul(rho));
+        sx.add_assign(&b.mul(rho));
+    }
+
+    (s.into_affine(), sx.into_affine())
+}
+
+/// Combines consecutive elements of `v`, whose ratio is tau for powers of tau
+fn power_pairs<G: CurveAffine>(v: &[G]) -> (G, G) {
+    merge_pairs(&v[..v.len() - 1], &v[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof_system::bn128::mpc::MPCParameters;
+    use crate::proof_system::bn128::tests::program;
+    use crate::proof_system::bn128::utils::bellman::Computation;
+    use crate::proof_system::{ProofSystem, G16};
+    use bellman::groth16::generate_parameters;
+    use pairing::bn256::{G1Uncompressed, G2Uncompressed};
+    use std::io::Cursor;
+
+    fn scalar(n: &str) -> Fr {
+        Fr::from_str(n).unwrap()
+    }
+
+    fn accumulator(size: usize, tau: Fr, alpha: Fr, beta: Fr) -> Vec<u8> {
+        let powers = |count: usize, factor: Fr| {
+            let mut p = factor;
+            (0..count)
+                .map(|_| {
+                    let current = p;
+                    p.mul_assign(&tau);
+                    current
+                })
+                .collect::<Vec<_>>()
+        };
+
+        fn push_g1(bytes: &mut Vec<u8>, s: &Fr) {
+            bytes.extend_from_slice(
+                G1Uncompressed::from_affine(G1Affine::one().mul(s.into_repr()).into_affine())
+                    .as_ref(),
+            )
+        }
+
+        let mut bytes = vec![0u8; HASH_SIZE];
+
+        for s in powers(2 * size - 1, Fr::one()) {
+            push_g1(&mut bytes, &s);
+        }
+        for s in powers(size, Fr::one()) {
+            bytes.extend_from_slice(
+                G2Uncompressed::from_affine(G2Affine::one().mul(s.into_repr()).into_affine())
+                    .as_ref(),
+            );
+        }
+        for s in powers(size, alpha) {
+            push_g1(&mut bytes, &s);
+        }
+        for s in powers(size, beta) {
+            push_g1(&mut bytes, &s);
+        }
+        bytes.extend_from_slice(
+            G2Uncompressed::from_affine(G2Affine::one().mul(beta.into_repr()).into_affine())
+                .as_ref(),
+        );
+
+        bytes
+    }
+
+    #[test]
+    fn matches_bellman() {
+        let (tau, alpha, beta) = (scalar("42"), scalar("43"), scalar("44"));
+
+        let bytes = accumulator(8, tau, alpha, beta);
+
+        let phase1 = Phase1::read(Cursor::new(&bytes), 8).unwrap();
+        assert_eq!(phase1.size(), 8);
+
+        // only the powers needed for the program are read
+        let domain_size = Phase1::domain_size(&program()).unwrap();
+        assert_eq!(domain_size, 4);
+        let streamed = Phase1::read(Cursor::new(&bytes), domain_size).unwrap();
+        assert_eq!(streamed.size(), 4);
+
+        let expected = generate_parameters::<Bn256, _>(
+            Computation::without_witness(&program()),
+            G1::one(),
+            G2::one(),
+            alpha,
+            beta,
+            Fr::one(),
+            Fr::one(),
+            tau,
+        )
+        .unwrap();
+
+        assert!(phase1.parameters(program()).unwrap() == expected);
+        assert!(streamed.parameters(program()).unwrap() == expected);
+    }
+
+    #[test]
+    fn setup_prove_verify() {
+        let bytes = accumulator(8, scalar("42"), scalar("43"), scalar("44"));
+        let phase1 = Phase1::read(Cursor::new(&bytes), 8).unwrap();
+
+        let keypair = G16 {}.setup_with_phase1(program(), &phase1).unwrap();
+
+        let witness = program().execute(&vec![FieldPrime::from(42)]).unwrap();
+        let proof = G16 {}
+            .generate_proof(program(), witness, &keypair.pk)
+            .unwrap();
+        assert!(G16 {}.verify(&keypair.vk, &proof, None).unwrap());
+
+        let initial = MPCParameters::from_phase1(program(), &phase1).unwrap();
+        let mut params = MPCParameters::from_phase1(program(), &phase1).unwrap();
+        params.contribute(b"").unwrap();
+        assert_eq!(params.verify(&program(), &initial).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn inconsistent_powers() {
+        let mut bytes = accumulator(4, scalar("42"), scalar("43"), scalar("44"));
+
+        // replace the third power of tau in G1 with the generator
+        let start = HASH_SIZE + 2 * G1_SIZE;
+        let one = G1Uncompressed::from_affine(G1Affine::one());
+        bytes[start..start + G1_SIZE].copy_from_slice(one.as_ref());
+
+        match Phase1::read(Cursor::new(&bytes), 4) {
+            Err(Error::InvalidPhase1(e)) => {
+                assert_eq!(e, "powers of tau in G1 are not consistent")
+            }
+            r => panic!("expected invalid phase 1, got {:?}", r.map(|p| p.size())),
+        }
+    }
+
+    #[test]
+    fn truncated_file() {
+        let bytes = accumulator(4, scalar("42"), scalar("43"), scalar("44"));
+
+        match Phase1::read(Cursor::new(&bytes[..bytes.len() - 1]), 4) {
+            Err(Error::InvalidPhase1(_)) => {}
+            r => panic!("expected invalid phase 1, got {:?}", r.map(|p| p.size())),
+        }
+    }
+
+    #[test]
+    fn circuit_too_large() {
+        let bytes = accumulator(2, scalar("42"), scalar("43"), scalar("44"));
+        let phase1 = Phase1::read(Cursor::new(&bytes), 4).unwrap();
+
+        // one constraint, plus one for each of the three inputs (one, argument and return value)
+        match phase1.parameters(program()) {
+            Err(Error::CircuitTooLarge {
+                constraints,
+                supported,
+            }) => {
+                assert_eq!(constraints, 4);
+                assert_eq!(supported, 2);
+            }
+            r => panic!("expected circuit too large, got {:?}", r.map(|_| ())),
+        }
+    }
+}
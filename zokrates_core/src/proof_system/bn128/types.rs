@@ -0,0 +1,453 @@
+//! Typed verification keys and proofs for the bn128 proving schemes.
+//!
+//! Keys and proofs are serialized to JSON. Points are given by their hex-encoded affine coordinates:
+//! * a G1 point is `["x", "y"]`
+//! * a G2 point is `[["x.c1", "x.c0"], ["y.c1", "y.c0"]]`
+//!
+//! For example, a G16 verification key looks like:
+//!
+//! ```json
+//! {
+//!   "alpha": ["0x0a...", "0x1b..."],
+//!   "beta": [["0x2c...", "0x3d..."], ["0x0e...", "0x1f..."]],
+//!   "gamma": [["0x20...", "0x01..."], ["0x12...", "0x23..."]],
+//!   "delta": [["0x04...", "0x15..."], ["0x26...", "0x07..."]],
+//!   "gamma_abc": [["0x18...", "0x29..."], ["0x0a...", "0x1b..."]]
+//! }
+//! ```
+//!
+//! Verification keys in the legacy `vk.name = values` text format can still be read.
+
+use crate::proof_system::bn128::utils::bellman::{
+    parse_fr_from_hex, parse_g1, parse_g1_from_hex, parse_g2, parse_g2_from_hex,
+};
+use crate::proof_system::Error;
+use lazy_static::lazy_static;
+use pairing::bn256::{self, Bn256, Fr};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Read};
+
+/// A G1 point given by its hex-encoded affine coordinates
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct G1Affine(pub String, pub String);
+
+/// A G2 point given by its hex-encoded affine coordinates, as `([x.c1, x.c0], [y.c1, y.c0])`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct G2Affine(pub (String, String), pub (String, String));
+
+impl G1Affine {
+    pub fn from_bellman(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> Self {
+        let (x, y) = parse_g1(e);
+        G1Affine(x, y)
+    }
+
+    pub fn to_bellman(&self) -> Result<bn256::G1Affine, String> {
+        parse_g1_from_hex(&[self.0.clone(), self.1.clone()])
+    }
+
+    fn from_values(values: &[String]) -> Result<Self, String> {
+        match values {
+            [x, y] => Ok(G1Affine(x.clone(), y.clone())),
+            _ => Err(format!(
+                "Expected 2 coordinates for a G1 point, found {}",
+                values.len()
+            )),
+        }
+    }
+}
+
+impl G2Affine {
+    pub fn from_bellman(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> Self {
+        let (x1, x0, y1, y0) = parse_g2(e);
+        G2Affine((x1, x0), (y1, y0))
+    }
+
+    pub fn to_bellman(&self) -> Result<bn256::G2Affine, String> {
+        parse_g2_from_hex(&[
+            (self.0).0.clone(),
+            (self.0).1.clone(),
+            (self.1).0.clone(),
+            (self.1).1.clone(),
+        ])
+    }
+
+    fn from_values(values: &[String]) -> Result<Self, String> {
+        match values {
+            [x1, x0, y1, y0] => Ok(G2Affine((x1.clone(), x0.clone()), (y1.clone(), y0.clone()))),
+            _ => Err(format!(
+                "Expected 4 coordinates for a G2 point, found {}",
+                values.len()
+            )),
+        }
+    }
+}
+
+/// A proof together with the public inputs it was generated for, as written to `proof.json`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Proof<T> {
+    pub proof: T,
+    pub inputs: Vec<String>,
+}
+
+impl<T: Serialize + DeserializeOwned> Proof<T> {
+    pub fn new(proof: T, inputs: Vec<String>) -> Self {
+        Proof { proof, inputs }
+    }
+
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        serde_json::from_reader(reader).map_err(|e| Error::MalformedProof(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn inputs_to_bellman(&self) -> Result<Vec<Fr>, Error> {
+        self.inputs
+            .iter()
+            .map(|i| parse_fr_from_hex(i).map_err(Error::MalformedProof))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref HEX_REGEX: Regex = Regex::new(r"0[xX][0-9a-fA-F]{64}").unwrap();
+}
+
+/// A verification key in the legacy `vk.name = values` text format, indexed by lowercase name
+struct VerificationKeyText(BTreeMap<String, Vec<String>>);
+
+impl VerificationKeyText {
+    fn read<R: BufRead>(reader: R) -> Result<Self, String> {
+        let mut values = BTreeMap::new();
+
+        for line in reader.lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let mut split = line.splitn(2, '=');
+            let key = split.next().unwrap().trim().to_lowercase();
+            if let Some(value) = split.next() {
+                values.insert(
+                    key,
+                    HEX_REGEX
+                        .find_iter(value)
+                        .map(|m| m.as_str().to_string())
+                        .collect(),
+                );
+            }
+        }
+
+        Ok(VerificationKeyText(values))
+    }
+
+    fn values(&self, key: &str) -> Result<&Vec<String>, String> {
+        self.0
+            .get(key)
+            .ok_or_else(|| format!("Missing `{}` in verification key", key))
+    }
+
+    fn g1(&self, key: &str) -> Result<G1Affine, String> {
+        G1Affine::from_values(self.values(key)?).map_err(|e| format!("Invalid `{}`: {}", key, e))
+    }
+
+    fn g2(&self, key: &str) -> Result<G2Affine, String> {
+        G2Affine::from_values(self.values(key)?).map_err(|e| format!("Invalid `{}`: {}", key, e))
+    }
+
+    /// Returns the G1 points `key[0]`, `key[1]`, ... up to the first missing index
+    fn g1_list(&self, key: &str) -> Result<Vec<G1Affine>, String> {
+        (0..)
+            .map(|i| format!("{}[{}]", key, i))
+            .take_while(|k| self.0.contains_key(k))
+            .map(|k| self.g1(&k))
+            .collect()
+    }
+}
+
+/// Reads a verification key from JSON, falling back to the legacy text format, and checks that all its points are valid
+fn read_verification_key<K, R>(
+    mut reader: R,
+    from_text: fn(&VerificationKeyText) -> Result<K, String>,
+    check: fn(&K) -> Result<(), String>,
+) -> Result<K, Error>
+where
+    K: DeserializeOwned,
+    R: Read,
+{
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+
+    let key = match content.trim_start().starts_with('{') {
+        true => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        false => VerificationKeyText::read(content.as_bytes()).and_then(|text| from_text(&text)),
+    }
+    .map_err(Error::MalformedKey)?;
+
+    check(&key).map_err(Error::MalformedKey)?;
+
+    Ok(key)
+}
+
+fn verification_key_to_json<K: Serialize>(key: &K) -> String {
+    // keys only hold strings, so serialization cannot fail
+    serde_json::to_string_pretty(key).unwrap()
+}
+
+fn check_g1s(points: &[G1Affine]) -> Result<(), String> {
+    points.iter().map(|p| p.to_bellman().map(|_| ())).collect()
+}
+
+fn check_g2s(points: &[G2Affine]) -> Result<(), String> {
+    points.iter().map(|p| p.to_bellman().map(|_| ())).collect()
+}
+
+/// Checks a list of input query points, which always holds at least the constant term
+fn check_query(name: &str, points: &[G1Affine]) -> Result<(), String> {
+    match points.len() {
+        0 => Err(format!("`{}` is empty", name)),
+        _ => check_g1s(points),
+    }
+}
+
+pub mod g16 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct VerificationKey {
+        pub alpha: G1Affine,
+        pub beta: G2Affine,
+        pub gamma: G2Affine,
+        pub delta: G2Affine,
+        pub gamma_abc: Vec<G1Affine>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct ProofPoints {
+        pub a: G1Affine,
+        pub b: G2Affine,
+        pub c: G1Affine,
+    }
+
+    impl VerificationKey {
+        pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+            read_verification_key(reader, Self::from_text, Self::check)
+        }
+
+        pub fn to_json(&self) -> String {
+            verification_key_to_json(self)
+        }
+
+        fn from_text(text: &VerificationKeyText) -> Result<Self, String> {
+            Ok(VerificationKey {
+                alpha: text.g1("vk.alpha")?,
+                beta: text.g2("vk.beta")?,
+                gamma: text.g2("vk.gamma")?,
+                delta: text.g2("vk.delta")?,
+                gamma_abc: text.g1_list("vk.gamma_abc")?,
+            })
+        }
+
+        fn check(&self) -> Result<(), String> {
+            check_g1s(&[self.alpha.clone()])?;
+            check_g2s(&[self.beta.clone(), self.gamma.clone(), self.delta.clone()])?;
+            check_query("gamma_abc", &self.gamma_abc)
+        }
+    }
+}
+
+pub mod gm17 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct VerificationKey {
+        pub h: G2Affine,
+        pub g_alpha: G1Affine,
+        pub h_beta: G2Affine,
+        pub g_gamma: G1Affine,
+        pub h_gamma: G2Affine,
+        pub query: Vec<G1Affine>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct ProofPoints {
+        pub a: G1Affine,
+        pub b: G2Affine,
+        pub c: G1Affine,
+    }
+
+    impl VerificationKey {
+        pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+            read_verification_key(reader, Self::from_text, Self::check)
+        }
+
+        pub fn to_json(&self) -> String {
+            verification_key_to_json(self)
+        }
+
+        fn from_text(text: &VerificationKeyText) -> Result<Self, String> {
+            Ok(VerificationKey {
+                h: text.g2("vk.h")?,
+                g_alpha: text.g1("vk.g_alpha")?,
+                h_beta: text.g2("vk.h_beta")?,
+                g_gamma: text.g1("vk.g_gamma")?,
+                h_gamma: text.g2("vk.h_gamma")?,
+                query: text.g1_list("vk.query")?,
+            })
+        }
+
+        fn check(&self) -> Result<(), String> {
+            check_g1s(&[self.g_alpha.clone(), self.g_gamma.clone()])?;
+            check_g2s(&[self.h.clone(), self.h_beta.clone(), self.h_gamma.clone()])?;
+            check_query("query", &self.query)
+        }
+    }
+}
+
+pub mod pghr13 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct VerificationKey {
+        pub a: G2Affine,
+        pub b: G1Affine,
+        pub c: G2Affine,
+        pub gamma: G2Affine,
+        pub gamma_beta_1: G1Affine,
+        pub gamma_beta_2: G2Affine,
+        pub z: G2Affine,
+        pub ic: Vec<G1Affine>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct ProofPoints {
+        pub a: G1Affine,
+        pub a_p: G1Affine,
+        pub b: G2Affine,
+        pub b_p: G1Affine,
+        pub c: G1Affine,
+        pub c_p: G1Affine,
+        pub h: G1Affine,
+        pub k: G1Affine,
+    }
+
+    impl VerificationKey {
+        pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+            read_verification_key(reader, Self::from_text, Self::check)
+        }
+
+        pub fn to_json(&self) -> String {
+            verification_key_to_json(self)
+        }
+
+        fn from_text(text: &VerificationKeyText) -> Result<Self, String> {
+            Ok(VerificationKey {
+                a: text.g2("vk.a")?,
+                b: text.g1("vk.b")?,
+                c: text.g2("vk.c")?,
+                gamma: text.g2("vk.gamma")?,
+                gamma_beta_1: text.g1("vk.gamma_beta_1")?,
+                gamma_beta_2: text.g2("vk.gamma_beta_2")?,
+                z: text.g2("vk.z")?,
+                ic: text.g1_list("vk.ic")?,
+            })
+        }
+
+        fn check(&self) -> Result<(), String> {
+            check_g1s(&[self.b.clone(), self.gamma_beta_1.clone()])?;
+            check_g2s(&[
+                self.a.clone(),
+                self.c.clone(),
+                self.gamma.clone(),
+                self.gamma_beta_2.clone(),
+                self.z.clone(),
+            ])?;
+            check_query("ic", &self.ic)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const G1_X: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    const G1_Y: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+    fn g1() -> G1Affine {
+        G1Affine(G1_X.to_string(), G1_Y.to_string())
+    }
+
+    fn g2() -> G2Affine {
+        G2Affine::from_bellman(&<bn256::G2Affine as pairing::CurveAffine>::one())
+    }
+
+    fn g16_key() -> g16::VerificationKey {
+        g16::VerificationKey {
+            alpha: g1(),
+            beta: g2(),
+            gamma: g2(),
+            delta: g2(),
+            gamma_abc: vec![g1(), g1()],
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let vk = g16_key();
+
+        assert_eq!(
+            g16::VerificationKey::read(vk.to_json().as_bytes()).unwrap(),
+            vk
+        );
+    }
+
+    #[test]
+    fn legacy_text_format() {
+        let vk = g16_key();
+        let g2 = format!(
+            "[{}, {}], [{}, {}]",
+            (vk.beta.0).0,
+            (vk.beta.0).1,
+            (vk.beta.1).0,
+            (vk.beta.1).1
+        );
+
+        // lines are not required to be in order
+        let text = format!(
+            "vk.beta = {g2}
+            vk.alpha = {x}, {y}
+            vk.gamma = {g2}
+            vk.delta = {g2}
+            vk.gamma_abc.len() = 2
+            vk.gamma_abc[1] = {x}, {y}
+            vk.gamma_abc[0] = {x}, {y}",
+            g2 = g2,
+            x = G1_X,
+            y = G1_Y
+        );
+
+        assert_eq!(g16::VerificationKey::read(text.as_bytes()).unwrap(), vk);
+    }
+
+    #[test]
+    fn missing_entry() {
+        let text = format!("vk.alpha = {}, {}", G1_X, G1_Y);
+
+        assert_eq!(
+            g16::VerificationKey::read(text.as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "Malformed key: Missing `vk.beta` in verification key"
+        );
+    }
+
+    #[test]
+    fn point_not_on_curve() {
+        let mut vk = g16_key();
+        vk.alpha = G1Affine(G1_X.to_string(), G1_X.to_string());
+
+        assert!(g16::VerificationKey::read(vk.to_json().as_bytes()).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 extern crate rand;
 
 use crate::ir::{CanonicalLinComb, Prog, Statement, Witness};
+use crate::proof_system::Error;
 use bellman::groth16::Proof;
 use bellman::groth16::{
     create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
-    Parameters,
+    Parameters, VerifyingKey,
 };
 use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
-use pairing::bn256::{Bn256, Fr};
+use pairing::bn256::{Bn256, Fq12, Fr, G1Affine, G2Affine, G1};
+use pairing::{CurveAffine, CurveProjective, Engine, Field as PairingField, PrimeField};
 use std::collections::BTreeMap;
 use zokrates_field::field::{Field, FieldPrime};
 
@@ -16,21 +18,23 @@ use crate::flat_absy::FlatVariable;
 
 pub use self::parse::*;
 
+/// A program to run the setup for or to prove, together with its witness when proving. The
+/// program is borrowed so that many proofs can be generated for it without copying it
 #[derive(Clone)]
-pub struct Computation<T: Field> {
-    program: Prog<T>,
+pub struct Computation<'a, T: Field> {
+    program: &'a Prog<T>,
     witness: Option<Witness<T>>,
 }
 
-impl<T: Field> Computation<T> {
-    pub fn with_witness(program: Prog<T>, witness: Witness<T>) -> Self {
+impl<'a, T: Field> Computation<'a, T> {
+    pub fn with_witness(program: &'a Prog<T>, witness: Witness<T>) -> Self {
         Computation {
             program,
             witness: Some(witness),
         }
     }
 
-    pub fn without_witness(program: Prog<T>) -> Self {
+    pub fn without_witness(program: &'a Prog<T>) -> Self {
         Computation {
             program,
             witness: None,
@@ -43,47 +47,46 @@ fn bellman_combination<CS: ConstraintSystem<Bn256>>(
     cs: &mut CS,
     symbols: &mut BTreeMap<FlatVariable, Variable>,
     witness: &mut Witness<FieldPrime>,
-) -> LinearCombination<Bn256> {
-    l.0.into_iter()
-        .map(|(k, v)| {
-            (
-                v.into_bellman(),
-                symbols
-                    .entry(k)
-                    .or_insert_with(|| {
-                        match k.is_output() {
-                            true => cs.alloc_input(
-                                || format!("{}", k),
-                                || {
-                                    Ok(witness
-                                        .0
-                                        .remove(&k)
-                                        .ok_or(SynthesisError::AssignmentMissing)?
-                                        .into_bellman())
-                                },
-                            ),
-                            false => cs.alloc(
-                                || format!("{}", k),
-                                || {
-                                    Ok(witness
-                                        .0
-                                        .remove(&k)
-                                        .ok_or(SynthesisError::AssignmentMissing)?
-                                        .into_bellman())
-                                },
-                            ),
-                        }
-                        .unwrap()
-                    })
-                    .clone(),
-            )
-        })
-        .fold(LinearCombination::zero(), |acc, e| acc + e)
+) -> Result<LinearCombination<Bn256>, SynthesisError> {
+    let mut combination = LinearCombination::zero();
+
+    for (k, v) in l.0 {
+        let variable = match symbols.get(&k) {
+            Some(variable) => variable.clone(),
+            None => {
+                let value = witness.0.remove(&k);
+                let variable = match k.is_output() {
+                    true => cs.alloc_input(
+                        || format!("{}", k),
+                        || {
+                            Ok(value
+                                .ok_or(SynthesisError::AssignmentMissing)?
+                                .into_bellman())
+                        },
+                    ),
+                    false => cs.alloc(
+                        || format!("{}", k),
+                        || {
+                            Ok(value
+                                .ok_or(SynthesisError::AssignmentMissing)?
+                                .into_bellman())
+                        },
+                    ),
+                }?;
+                symbols.insert(k, variable.clone());
+                variable
+            }
+        };
+
+        combination = combination + (v.into_bellman(), variable);
+    }
+
+    Ok(combination)
 }
 
 impl Prog<FieldPrime> {
     pub fn synthesize<CS: ConstraintSystem<Bn256>>(
-        self,
+        &self,
         cs: &mut CS,
         witness: Option<Witness<FieldPrime>>,
     ) -> Result<(), SynthesisError> {
@@ -94,43 +97,30 @@ impl Prog<FieldPrime> {
 
         assert!(symbols.insert(FlatVariable::one(), CS::one()).is_none());
 
-        symbols.extend(
-            self.main
-                .arguments
-                .iter()
-                .zip(self.private)
-                .enumerate()
-                .map(|(index, (var, private))| {
-                    let wire = match private {
-                        true => cs.alloc(
-                            || format!("PRIVATE_INPUT_{}", index),
-                            || {
-                                Ok(witness
-                                    .0
-                                    .remove(&var)
-                                    .ok_or(SynthesisError::AssignmentMissing)?
-                                    .into_bellman())
-                            },
-                        ),
-                        false => cs.alloc_input(
-                            || format!("PUBLIC_INPUT_{}", index),
-                            || {
-                                Ok(witness
-                                    .0
-                                    .remove(&var)
-                                    .ok_or(SynthesisError::AssignmentMissing)?
-                                    .into_bellman())
-                            },
-                        ),
-                    }
-                    .unwrap();
-                    (var.clone(), wire)
-                }),
-        );
-
-        let main = self.main;
+        for (index, (var, private)) in self.main.arguments.iter().zip(&self.private).enumerate() {
+            let value = witness.0.remove(&var);
+            let wire = match *private {
+                true => cs.alloc(
+                    || format!("PRIVATE_INPUT_{}", index),
+                    || {
+                        Ok(value
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_bellman())
+                    },
+                ),
+                false => cs.alloc_input(
+                    || format!("PUBLIC_INPUT_{}", index),
+                    || {
+                        Ok(value
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_bellman())
+                    },
+                ),
+            }?;
+            symbols.insert(var.clone(), wire);
+        }
 
-        for statement in main.statements {
+        for statement in &self.main.statements {
             match statement {
                 Statement::Constraint(quad, lin) => {
                     let a = &bellman_combination(
@@ -138,15 +128,19 @@ impl Prog<FieldPrime> {
                         cs,
                         &mut symbols,
                         &mut witness,
-                    );
+                    )?;
                     let b = &bellman_combination(
                         quad.right.clone().as_canonical(),
                         cs,
                         &mut symbols,
                         &mut witness,
-                    );
-                    let c =
-                        &bellman_combination(lin.as_canonical(), cs, &mut symbols, &mut witness);
+                    )?;
+                    let c = &bellman_combination(
+                        lin.clone().as_canonical(),
+                        cs,
+                        &mut symbols,
+                        &mut witness,
+                    )?;
 
                     cs.enforce(|| "Constraint", |lc| lc + a, |lc| lc + b, |lc| lc + c);
                 }
@@ -158,53 +152,173 @@ impl Prog<FieldPrime> {
     }
 }
 
-impl Computation<FieldPrime> {
-    pub fn prove(self, params: &Parameters<Bn256>) -> Proof<Bn256> {
-        let rng = &mut thread_rng();
-        let proof = create_random_proof(self.clone(), params, rng).unwrap();
-
-        let pvk = prepare_verifying_key(&params.vk);
-
+impl<'a> Computation<'a, FieldPrime> {
+    pub fn prove(self, params: &Parameters<Bn256>) -> Result<Proof<Bn256>, Error> {
         // extract public inputs
         let public_inputs = self.public_inputs_values();
 
-        assert!(verify_proof(&pvk, &proof, &public_inputs).unwrap());
+        let rng = &mut thread_rng();
+        let proof = create_random_proof(self, params, rng)?;
+
+        let pvk = prepare_verifying_key(&params.vk);
 
-        proof
+        match verify_proof(&pvk, &proof, &public_inputs)? {
+            true => Ok(proof),
+            false => Err(Error::MalformedKey(String::from(
+                "generated proof does not verify against the proving key",
+            ))),
+        }
     }
 
     pub fn public_inputs_values(&self) -> Vec<Fr> {
+        let witness = self.witness.as_ref().unwrap();
+
         self.program
             .main
             .arguments
-            .clone()
             .iter()
-            .zip(self.program.private.clone())
-            .filter(|(_, p)| !p)
-            .map(|(a, _)| a)
-            .map(|v| self.witness.clone().unwrap().0.get(v).unwrap().clone())
-            .chain(self.witness.clone().unwrap().return_values())
-            .map(|v| v.clone().into_bellman())
+            .zip(&self.program.private)
+            .filter(|(_, p)| !**p)
+            .map(|(a, _)| witness.0.get(a).unwrap().clone())
+            .chain(witness.return_values())
+            .map(|v| v.into_bellman())
             .collect()
     }
 
-    pub fn setup(self) -> Parameters<Bn256> {
+    pub fn setup(self) -> Result<Parameters<Bn256>, Error> {
         let rng = &mut thread_rng();
         // run setup phase
-        generate_random_parameters(self, rng).unwrap()
+        Ok(generate_random_parameters(self, rng)?)
     }
 }
 
-impl Circuit<Bn256> for Computation<FieldPrime> {
+impl<'a> Circuit<Bn256> for Computation<'a, FieldPrime> {
     fn synthesize<CS: ConstraintSystem<Bn256>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
         self.program.synthesize(cs, self.witness)
     }
 }
 
+/// Computes `query[0] + sum(inputs[i] * query[i + 1])`, the input commitment used by the pairing-based verifiers
+pub fn accumulate_inputs(query: &[G1Affine], inputs: &[Fr]) -> G1Affine {
+    let mut acc = query[0].into_projective();
+    for (q, i) in query.iter().skip(1).zip(inputs.iter()) {
+        acc.add_assign(&q.mul(i.into_repr()));
+    }
+    acc.into_affine()
+}
+
+/// Returns the sum of two affine G1 points
+pub fn g1_add(a: &G1Affine, b: &G1Affine) -> G1Affine {
+    let mut sum = a.into_projective();
+    sum.add_assign_mixed(b);
+    sum.into_affine()
+}
+
+/// Returns the negation of an affine G1 point
+pub fn g1_neg(a: &G1Affine) -> G1Affine {
+    let mut neg = a.clone();
+    neg.negate();
+    neg
+}
+
+/// Returns the sum of two affine G2 points
+pub fn g2_add(a: &G2Affine, b: &G2Affine) -> G2Affine {
+    let mut sum = a.into_projective();
+    sum.add_assign_mixed(b);
+    sum.into_affine()
+}
+
+/// Checks that the product of the pairings `e(g1, g2)` over all `pairs` is one
+pub fn pairing_product_is_one(pairs: &[(G1Affine, G2Affine)]) -> bool {
+    let prepared: Vec<_> = pairs
+        .iter()
+        .map(|(g1, g2)| (g1.prepare(), g2.prepare()))
+        .collect();
+    let refs: Vec<_> = prepared.iter().map(|(g1, g2)| (g1, g2)).collect();
+
+    Bn256::final_exponentiation(&Bn256::miller_loop(refs.iter())) == Some(Fq12::one())
+}
+
+/// Verifies Groth16 proofs for the same verification key, and returns whether each of them is
+/// valid. All proofs are first checked together with one randomized pairing product. If that
+/// check fails, the batch is split in halves until the invalid proofs are isolated
+pub fn batch_verify(vk: &VerifyingKey<Bn256>, proofs: &[(Proof<Bn256>, Vec<Fr>)]) -> Vec<bool> {
+    let mut valid = vec![false; proofs.len()];
+
+    // proofs with the wrong number of inputs can never be valid
+    let candidates: Vec<usize> = (0..proofs.len())
+        .filter(|i| proofs[*i].1.len() + 1 == vk.ic.len())
+        .collect();
+
+    batch_verify_indices(vk, proofs, &candidates, &mut valid);
+
+    valid
+}
+
+fn batch_verify_indices(
+    vk: &VerifyingKey<Bn256>,
+    proofs: &[(Proof<Bn256>, Vec<Fr>)],
+    indices: &[usize],
+    valid: &mut [bool],
+) {
+    if indices.is_empty() {
+        return;
+    }
+
+    if randomized_check(vk, proofs, indices) {
+        for i in indices {
+            valid[*i] = true;
+        }
+    } else if indices.len() > 1 {
+        let (left, right) = indices.split_at(indices.len() / 2);
+        batch_verify_indices(vk, proofs, left, valid);
+        batch_verify_indices(vk, proofs, right, valid);
+    }
+}
+
+/// Checks `e(r_i * A_i, B_i) == e(r_i * alpha, beta) * e(r_i * IC_i, gamma) * e(r_i * C_i, delta)`
+/// multiplied over the given proofs, for random `r_i`, so that a valid product means all proofs
+/// are valid with overwhelming probability
+fn randomized_check(
+    vk: &VerifyingKey<Bn256>,
+    proofs: &[(Proof<Bn256>, Vec<Fr>)],
+    indices: &[usize],
+) -> bool {
+    let rng = &mut thread_rng();
+
+    let mut pairs = vec![];
+    let mut r_sum = Fr::zero();
+    let mut ic_sum = G1::zero();
+    let mut c_sum = G1::zero();
+
+    for i in indices {
+        let (proof, inputs) = &proofs[*i];
+        let r = Fr::rand(rng);
+
+        pairs.push((proof.a.mul(r.into_repr()).into_affine(), proof.b));
+        ic_sum.add_assign(&accumulate_inputs(&vk.ic, inputs).mul(r.into_repr()));
+        c_sum.add_assign(&proof.c.mul(r.into_repr()));
+        r_sum.add_assign(&r);
+    }
+
+    let mut alpha_sum = vk.alpha_g1.mul(r_sum.into_repr());
+    alpha_sum.negate();
+    ic_sum.negate();
+    c_sum.negate();
+
+    pairs.push((alpha_sum.into_affine(), vk.beta_g2));
+    pairs.push((ic_sum.into_affine(), vk.gamma_g2));
+    pairs.push((c_sum.into_affine(), vk.delta_g2));
+
+    pairing_product_is_one(&pairs)
+}
+
 mod parse {
     use lazy_static::lazy_static;
 
     use super::*;
+    use pairing::bn256::{Fq, G1Uncompressed, G2Uncompressed};
+    use pairing::{EncodedPoint, PrimeFieldRepr};
     use regex::Regex;
 
     lazy_static! {
@@ -222,7 +336,7 @@ mod parse {
         static ref FR_REGEX: Regex = Regex::new(r"Fr\((?P<x>0[xX][0-9a-fA-F]{64})\)").unwrap();
     }
 
-    fn parse_g1(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> (String, String) {
+    pub fn parse_g1(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> (String, String) {
         let raw_e = e.to_string();
 
         let captures = G1_REGEX.captures(&raw_e).unwrap();
@@ -233,7 +347,7 @@ mod parse {
         )
     }
 
-    fn parse_g2(
+    pub fn parse_g2(
         e: &<Bn256 as bellman::pairing::Engine>::G2Affine,
     ) -> (String, String, String, String) {
         let raw_e = e.to_string();
@@ -248,7 +362,7 @@ mod parse {
         )
     }
 
-    fn parse_fr(e: &Fr) -> String {
+    pub fn parse_fr(e: &Fr) -> String {
         let raw_e = e.to_string();
 
         let captures = FR_REGEX.captures(&raw_e).unwrap();
@@ -256,37 +370,58 @@ mod parse {
         captures.name(&"x").unwrap().as_str().to_string()
     }
 
-    pub fn parse_g1_json(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> String {
-        let parsed = parse_g1(e);
-
-        format!("[\"{}\", \"{}\"]", parsed.0, parsed.1)
-    }
+    /// Builds a G1 point from its hex coordinates `[x, y]`, checking that it is on the curve
+    pub fn parse_g1_from_hex(
+        values: &[String],
+    ) -> Result<<Bn256 as bellman::pairing::Engine>::G1Affine, String> {
+        if values.len() != 2 {
+            return Err(format!(
+                "Expected 2 coordinates for a G1 point, found {}",
+                values.len()
+            ));
+        }
 
-    pub fn parse_g2_json(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> String {
-        let parsed = parse_g2(e);
+        let mut encoded = G1Uncompressed::empty();
+        {
+            let mut writer = encoded.as_mut();
+            for v in values {
+                Fq::from_hex(v)?
+                    .into_repr()
+                    .write_be(&mut writer)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
 
-        format!(
-            "[[\"{}\", \"{}\"], [\"{}\", \"{}\"]]",
-            parsed.0, parsed.1, parsed.2, parsed.3,
-        )
+        encoded.into_affine().map_err(|e| e.to_string())
     }
 
-    pub fn parse_fr_json(e: &Fr) -> String {
-        let parsed = parse_fr(e);
-
-        format!("\"{}\"", parsed)
-    }
+    /// Builds a G2 point from its hex coordinates `[x1, x0, y1, y0]`, checking that it is on the curve
+    pub fn parse_g2_from_hex(
+        values: &[String],
+    ) -> Result<<Bn256 as bellman::pairing::Engine>::G2Affine, String> {
+        if values.len() != 4 {
+            return Err(format!(
+                "Expected 4 coordinates for a G2 point, found {}",
+                values.len()
+            ));
+        }
 
-    pub fn parse_g1_hex(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> String {
-        let parsed = parse_g1(e);
+        let mut encoded = G2Uncompressed::empty();
+        {
+            let mut writer = encoded.as_mut();
+            for v in values {
+                Fq::from_hex(v)?
+                    .into_repr()
+                    .write_be(&mut writer)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
 
-        format!("{}, {}", parsed.0, parsed.1)
+        encoded.into_affine().map_err(|e| e.to_string())
     }
 
-    pub fn parse_g2_hex(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> String {
-        let parsed = parse_g2(e);
-
-        format!("[{}, {}], [{}, {}]", parsed.0, parsed.1, parsed.2, parsed.3,)
+    pub fn parse_fr_from_hex(value: &str) -> Result<Fr, String> {
+        Fr::from_hex(value)
     }
 }
 
@@ -308,16 +443,17 @@ mod tests {
                     arguments: vec![],
                     returns: vec![],
                     statements: vec![],
+                    debug: None,
                 },
                 private: vec![],
                 signature: Signature::new(),
             };
 
             let witness = program.clone().execute(&vec![]).unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -331,6 +467,7 @@ mod tests {
                         FlatVariable::new(0).into(),
                         FlatVariable::public(0).into(),
                     )],
+                    debug: None,
                 },
                 private: vec![true],
                 signature: Signature::new()
@@ -339,10 +476,10 @@ mod tests {
             };
 
             let witness = program.clone().execute(&vec![FieldPrime::from(0)]).unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -356,6 +493,7 @@ mod tests {
                         FlatVariable::new(0).into(),
                         FlatVariable::public(0).into(),
                     )],
+                    debug: None,
                 },
                 private: vec![false],
                 signature: Signature::new()
@@ -364,10 +502,10 @@ mod tests {
             };
 
             let witness = program.clone().execute(&vec![FieldPrime::from(0)]).unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -381,16 +519,17 @@ mod tests {
                         FlatVariable::one().into(),
                         FlatVariable::public(0).into(),
                     )],
+                    debug: None,
                 },
                 private: vec![],
                 signature: Signature::new().outputs(vec![Type::FieldElement]),
             };
 
             let witness = program.clone().execute(&vec![]).unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -416,6 +555,7 @@ mod tests {
                             FlatVariable::public(1).into(),
                         ),
                     ],
+                    debug: None,
                 },
                 private: vec![true, false],
                 signature: Signature::new()
@@ -427,10 +567,10 @@ mod tests {
                 .clone()
                 .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
                 .unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -444,6 +584,7 @@ mod tests {
                         (LinComb::from(FlatVariable::new(42)) + LinComb::one()).into(),
                         FlatVariable::public(0).into(),
                     )],
+                    debug: None,
                 },
                 private: vec![false],
                 signature: Signature::new()
@@ -452,10 +593,10 @@ mod tests {
             };
 
             let witness = program.clone().execute(&vec![FieldPrime::from(3)]).unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
         }
 
         #[test]
@@ -471,6 +612,7 @@ mod tests {
                         .into(),
                         FlatVariable::public(0).into(),
                     )],
+                    debug: None,
                 },
                 private: vec![true, false],
                 signature: Signature::new()
@@ -482,10 +624,41 @@ mod tests {
                 .clone()
                 .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
                 .unwrap();
-            let computation = Computation::with_witness(program, witness);
+            let computation = Computation::with_witness(&program, witness);
+
+            let params = computation.clone().setup().unwrap();
+            let _proof = computation.prove(&params).unwrap();
+        }
+    }
+
+    mod batch_verify {
+        use super::*;
+        use crate::proof_system::bn128::tests::program;
 
-            let params = computation.clone().setup();
-            let _proof = computation.prove(&params);
+        #[test]
+        fn reports_invalid_proofs() {
+            let program = program();
+
+            let params = Computation::without_witness(&program).setup().unwrap();
+
+            let mut proofs: Vec<_> = (0..5)
+                .map(|i| {
+                    let witness = program.clone().execute(&vec![FieldPrime::from(i)]).unwrap();
+                    let computation = Computation::with_witness(&program, witness);
+                    let inputs = computation.public_inputs_values();
+                    (computation.prove(&params).unwrap(), inputs)
+                })
+                .collect();
+
+            // claim wrong inputs for the second proof, and too few inputs for the fourth one
+            proofs[1].1[1] = proofs[2].1[1];
+            proofs[3].1.pop();
+
+            assert_eq!(
+                batch_verify(&params.vk, &proofs),
+                vec![true, false, true, false, true]
+            );
+            assert_eq!(batch_verify(&params.vk, &[]), Vec::<bool>::new());
         }
     }
 }
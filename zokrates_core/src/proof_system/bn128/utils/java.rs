@@ -1,5 +1,470 @@
+use crate::proof_system::bn128::types::{G1Affine, G2Affine};
+use crate::proof_system::Error;
+use num_bigint::BigUint;
+use std::iter;
+
+fn java_hex(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value.trim_start_matches("0x").trim_start_matches("0X")
+    )
+}
+
+/// Formats a G1 point as the arguments of a `G1Point`
+pub fn java_g1(p: &G1Affine) -> String {
+    format!("{}, {}", java_hex(&p.0), java_hex(&p.1))
+}
+
+/// Formats a G2 point as the arguments of a `G2Point`, which takes the `c0` coefficients first
+pub fn java_g2(p: &G2Affine) -> String {
+    format!(
+        "{}, {}, {}, {}",
+        java_hex(&(p.0).1),
+        java_hex(&(p.0).0),
+        java_hex(&(p.1).1),
+        java_hex(&(p.1).0)
+    )
+}
+
+/// Size in bytes of a field element in the AVM verifiers, `Fp.ELEMENT_SIZE`
+const JAVA_ELEMENT_SIZE: usize = 32;
+
+/// Encodes a hex-encoded coordinate as a big-endian integer of `Fp.ELEMENT_SIZE` bytes
+fn java_bytes(value: &str) -> Result<Vec<u8>, Error> {
+    let hex = value.trim_start_matches("0x").trim_start_matches("0X");
+    let bytes = BigUint::parse_bytes(hex.as_bytes(), 16)
+        .ok_or_else(|| Error::MalformedProof(format!("Invalid coordinate `{}`", value)))?
+        .to_bytes_be();
+
+    if bytes.len() > JAVA_ELEMENT_SIZE {
+        return Err(Error::MalformedProof(format!(
+            "Coordinate `{}` does not fit in {} bytes",
+            value, JAVA_ELEMENT_SIZE
+        )));
+    }
+
+    let mut res = vec![0; JAVA_ELEMENT_SIZE - bytes.len()];
+    res.extend(bytes);
+    Ok(res)
+}
+
+/// Encodes a G1 point as `G1.serialize` does
+pub fn java_g1_bytes(p: &G1Affine) -> Result<Vec<u8>, Error> {
+    let mut res = java_bytes(&p.0)?;
+    res.extend(java_bytes(&p.1)?);
+    Ok(res)
+}
+
+/// Encodes a G2 point as `G2.serialize` does, with the `c0` coefficients first as in `java_g2`
+pub fn java_g2_bytes(p: &G2Affine) -> Result<Vec<u8>, Error> {
+    let mut res = java_bytes(&(p.0).1)?;
+    res.extend(java_bytes(&(p.0).0)?);
+    res.extend(java_bytes(&(p.1).1)?);
+    res.extend(java_bytes(&(p.1).0)?);
+    Ok(res)
+}
+
+/// A proof in the layout read by `Proof.deserialize` in the AVM verifiers, with its public inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct JavaProof {
+    pub proof: Vec<u8>,
+    pub inputs: Vec<BigUint>,
+}
+
+/// Tags of the Aion ABI encoding
+const ABI_INT: u8 = 0x05;
+const ABI_BYTE_ARRAY: u8 = 0x11;
+const ABI_STRING: u8 = 0x21;
+const ABI_BIG_INTEGER: u8 = 0x23;
+const ABI_ARRAY: u8 = 0x31;
+
+impl JavaProof {
+    /// Creates a proof from its encoded points, in the order of `Proof.deserialize`, and its
+    /// hex-encoded public inputs
+    pub fn new(points: &[Vec<u8>], inputs: &[String]) -> Result<Self, Error> {
+        Ok(JavaProof {
+            proof: points.concat(),
+            inputs: inputs
+                .iter()
+                .map(|i| {
+                    let hex = i.trim_start_matches("0x").trim_start_matches("0X");
+                    BigUint::parse_bytes(hex.as_bytes(), 16)
+                        .ok_or_else(|| Error::MalformedProof(format!("Invalid input `{}`", i)))
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Encodes a call to `verify(BigInteger[] input, byte[] proof)` in the Aion ABI, or to
+    /// `verify(int circuitId, BigInteger[] input, byte[] proof)` of a registry if `circuit_id`
+    /// is given
+    pub fn call_data(&self, circuit_id: Option<i32>) -> Vec<u8> {
+        let mut res = vec![];
+
+        abi_bytes(&mut res, ABI_STRING, b"verify");
+        if let Some(id) = circuit_id {
+            res.push(ABI_INT);
+            res.extend(&id.to_be_bytes());
+        }
+        res.push(ABI_ARRAY);
+        res.push(ABI_BIG_INTEGER);
+        res.extend(&(self.inputs.len() as u16).to_be_bytes());
+        for input in &self.inputs {
+            // `BigInteger.toByteArray` is a two's complement, so positive values with the most
+            // significant bit set get a leading zero byte
+            let mut bytes = input.to_bytes_be();
+            if bytes[0] & 0x80 != 0 {
+                bytes.insert(0, 0);
+            }
+            res.push(ABI_BIG_INTEGER);
+            res.push(bytes.len() as u8);
+            res.extend(bytes);
+        }
+        abi_bytes(&mut res, ABI_BYTE_ARRAY, &self.proof);
+
+        res
+    }
+}
+
+/// Encodes a string or byte array, prefixed by its tag and its length on two bytes
+fn abi_bytes(res: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    res.push(tag);
+    res.extend(&(bytes.len() as u16).to_be_bytes());
+    res.extend(bytes);
+}
+
+pub const JAVA_DEFAULT_PACKAGE: &str = "org.oan.tetryon";
+pub const JAVA_DEFAULT_CLASS: &str = "Verifier";
+
+/// The helper classes shared by the AVM verifiers, with their sources
+const JAVA_LIB: [(&str, &str); 8] = [
+    ("Fp", JAVA_LIB_FP),
+    ("Fp2", JAVA_LIB_FP2),
+    ("G1", JAVA_LIB_G1),
+    ("G1Point", JAVA_LIB_G1POINT),
+    ("G2", JAVA_LIB_G2),
+    ("G2Point", JAVA_LIB_G2POINT),
+    ("Pairing", JAVA_LIB_PAIRING),
+    ("Util", JAVA_LIB_UTIL),
+];
+
+/// The names used in the Java sources of an AVM verifier: the package of all classes, the name
+/// of the verifier class, and a prefix added to the names of the helper classes
+#[derive(Debug, Clone, PartialEq)]
+pub struct JavaNames {
+    package: String,
+    class: String,
+    prefix: String,
+}
+
+impl Default for JavaNames {
+    fn default() -> Self {
+        JavaNames {
+            package: JAVA_DEFAULT_PACKAGE.to_string(),
+            class: JAVA_DEFAULT_CLASS.to_string(),
+            prefix: String::new(),
+        }
+    }
+}
+
+fn is_java_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
+}
+
+impl JavaNames {
+    pub fn new(package: &str, class: &str, prefix: &str) -> Result<Self, String> {
+        if !package.split('.').all(is_java_identifier) {
+            return Err(format!("`{}` is not a valid Java package name", package));
+        }
+        if !is_java_identifier(class) {
+            return Err(format!("`{}` is not a valid Java class name", class));
+        }
+        if !prefix.is_empty() && !is_java_identifier(prefix) {
+            return Err(format!(
+                "`{}` is not a valid Java class name prefix",
+                prefix
+            ));
+        }
+
+        let names = JavaNames {
+            package: package.to_string(),
+            class: class.to_string(),
+            prefix: prefix.to_string(),
+        };
+
+        if JAVA_LIB
+            .iter()
+            .any(|(name, _)| names.helper(name) == names.class)
+        {
+            return Err(format!(
+                "`{}` is already the name of a helper class, choose another class name or prefix",
+                class
+            ));
+        }
+
+        Ok(names)
+    }
+
+    /// Returns the file name of the verifier class
+    pub fn file_name(&self) -> String {
+        format!("{}.java", self.class)
+    }
+
+    /// Returns the names of the verifier of the circuit `id` in a registry
+    pub fn circuit(&self, id: usize) -> JavaNames {
+        JavaNames {
+            class: format!("{}Circuit{}", self.class, id),
+            ..self.clone()
+        }
+    }
+
+    fn helper(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    /// Sets the package and class name in a Java template, and prefixes the references to the
+    /// helper classes
+    pub fn apply(&self, template: &str) -> String {
+        let mut res = String::with_capacity(template.len());
+        let mut token = String::new();
+
+        for c in template.chars().chain(iter::once('\n')) {
+            if c.is_alphanumeric() || c == '_' {
+                token.push(c);
+                continue;
+            }
+            match JAVA_LIB.iter().find(|(name, _)| *name == token) {
+                Some((name, _)) => res.push_str(&self.helper(name)),
+                None => res.push_str(&token),
+            }
+            token.clear();
+            res.push(c);
+        }
+        res.pop();
+
+        res.replace("<%package%>", &self.package)
+            .replace("<%class%>", &self.class)
+    }
+}
+
+/// Returns the file names and sources of the helper classes used by the AVM verifiers
+pub fn java_lib(names: &JavaNames) -> Vec<(String, String)> {
+    JAVA_LIB
+        .iter()
+        .map(|(name, source)| (format!("{}.java", names.helper(name)), names.apply(source)))
+        .collect()
+}
+
+/// Sets the `@Callable` entry point of a verifier template, which is left out for the verifiers
+/// of a registry as these are called through the registry contract
+pub fn java_entry_point(template: &str, is_callable: bool) -> String {
+    template.replace(
+        "<%entry_point%>",
+        if is_callable { JAVA_ENTRY_POINT } else { "" },
+    )
+}
+
+/// Generates a registry contract dispatching the proofs for the circuit `id` to the verifier
+/// exported with the names `names.circuit(id)`, for each of the `circuit_count` circuits
+pub fn java_registry(names: &JavaNames, circuit_count: usize) -> String {
+    let cases = (0..circuit_count)
+        .map(|id| {
+            let class = names.circuit(id).class;
+            format!(
+                "case {}:\n                return {}.verify(input, {}.Proof.deserialize(proof));",
+                id, class, class
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n            ");
+
+    names.apply(
+        &JAVA_REGISTRY_TEMPLATE
+            .replace("<%circuit_count%>", &circuit_count.to_string())
+            .replace("<%circuit_cases%>", &cases),
+    )
+}
+
+/// Generates a JUnit test deploying the verifier, or the registry of `circuit_count` circuits, with
+/// the AVM tooling. It checks that each of the encoded calls `call_data` succeeds, and fails once
+/// its proof is tampered with
+pub fn java_test(
+    names: &JavaNames,
+    circuit_count: usize,
+    call_data: &[Vec<u8>],
+) -> (String, String) {
+    // the verifiers of the circuits are only separate classes in a registry
+    let circuits = match circuit_count {
+        1 => vec![],
+        _ => (0..circuit_count)
+            .map(|id| names.circuit(id).class)
+            .collect(),
+    };
+
+    let classes = circuits
+        .into_iter()
+        .chain(JAVA_LIB.iter().map(|(name, _)| name.to_string()))
+        .map(|class| format!("{}.class", class))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let tests = call_data
+        .iter()
+        .enumerate()
+        .map(|(i, call_data)| {
+            JAVA_TEST_CASE_TEMPLATE
+                .replace("<%index%>", &i.to_string())
+                .replace(
+                    "<%call_data%>",
+                    &call_data
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>(),
+                )
+        })
+        .collect::<String>();
+
+    (
+        format!("{}Test.java", names.class),
+        names.apply(
+            &JAVA_TEST_TEMPLATE
+                .replace("<%classes%>", &classes)
+                .replace("<%tests%>", &tests),
+        ),
+    )
+}
+
+const JAVA_ENTRY_POINT: &str = r#"
+    @Callable
+    public static boolean verify(BigInteger[] input, byte[] proof) {
+        Blockchain.println("verify() called");
+
+        try {
+            if (verify(input, Proof.deserialize(proof))) {
+                Blockchain.log("VerifySnark".getBytes(), BigInteger.ONE.toByteArray());
+                return true;
+            }
+        } catch (Exception e) {
+            Blockchain.println("verify() failed with exception: " + e.getMessage());
+        }
+
+        Blockchain.log("VerifySnark".getBytes(), BigInteger.ZERO.toByteArray());
+        return false;
+    }
+"#;
+
+const JAVA_REGISTRY_TEMPLATE: &str = r#"// This file is MIT Licensed
+package <%package%>;
+
+import avm.Blockchain;
+import org.aion.avm.tooling.abi.Callable;
+
+import java.math.BigInteger;
+
+/**
+ * Verifier smart contract for several circuits, identified by their index. Auto-generated by Zokrates.
+ */
+@SuppressWarnings({"WeakerAccess", "unused"})
+public class <%class%> {
+    public static final int CIRCUIT_COUNT = <%circuit_count%>;
+
+    public static boolean verifyCircuit(int circuitId, BigInteger[] input, byte[] proof) throws Exception {
+        switch (circuitId) {
+            <%circuit_cases%>
+            default:
+                return false;
+        }
+    }
+
+    @Callable
+    public static boolean verify(int circuitId, BigInteger[] input, byte[] proof) {
+        Blockchain.println("verify() called for circuit " + circuitId);
+
+        try {
+            if (verifyCircuit(circuitId, input, proof)) {
+                Blockchain.log("VerifySnark".getBytes(), BigInteger.valueOf(circuitId).toByteArray(), BigInteger.ONE.toByteArray());
+                return true;
+            }
+        } catch (Exception e) {
+            Blockchain.println("verify() failed with exception: " + e.getMessage());
+        }
+
+        Blockchain.log("VerifySnark".getBytes(), BigInteger.valueOf(circuitId).toByteArray(), BigInteger.ZERO.toByteArray());
+        return false;
+    }
+}
+"#;
+
+const JAVA_TEST_TEMPLATE: &str = r#"// This file is MIT Licensed
+package <%package%>;
+
+import avm.Address;
+import org.aion.avm.tooling.AvmRule;
+import org.junit.Assert;
+import org.junit.Before;
+import org.junit.Rule;
+import org.junit.Test;
+
+import java.math.BigInteger;
+
+/**
+ * Deploys the verifier locally and checks it against sample proofs. Auto-generated by Zokrates.
+ */
+public class <%class%>Test {
+    @Rule
+    public AvmRule avmRule = new AvmRule(false);
+
+    private final Address sender = avmRule.getPreminedAccount();
+    private Address contract;
+
+    @Before
+    public void deploy() {
+        byte[] dapp = avmRule.getDappBytes(<%class%>.class, null, <%classes%>);
+        AvmRule.ResultWrapper result = avmRule.deploy(sender, BigInteger.ZERO, dapp);
+        Assert.assertTrue(result.getReceiptStatus().isSuccess());
+        contract = result.getDappAddress();
+    }
+
+    private boolean verify(byte[] callData) {
+        AvmRule.ResultWrapper result = avmRule.call(sender, contract, BigInteger.ZERO, callData);
+        return result.getReceiptStatus().isSuccess() && Boolean.TRUE.equals(result.getDecodedReturnData());
+    }
+
+    private static byte[] fromHex(String hex) {
+        byte[] bytes = new byte[hex.length() / 2];
+        for (int i = 0; i < bytes.length; i++) {
+            bytes[i] = (byte) Integer.parseInt(hex.substring(2 * i, 2 * i + 2), 16);
+        }
+        return bytes;
+    }
+<%tests%>}
+"#;
+
+const JAVA_TEST_CASE_TEMPLATE: &str = r#"
+    private static final String CALL_DATA_<%index%> = "<%call_data%>";
+
+    @Test
+    public void proofVerifies<%index%>() {
+        Assert.assertTrue(verify(fromHex(CALL_DATA_<%index%>)));
+    }
+
+    @Test
+    public void tamperedProofFails<%index%>() {
+        byte[] callData = fromHex(CALL_DATA_<%index%>);
+        // the proof is the last argument of the call
+        callData[callData.length - 1] ^= 1;
+        Assert.assertFalse(verify(callData));
+    }
+"#;
+
 pub const JAVA_LIB_FP: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import java.math.BigInteger;
 
@@ -52,7 +517,7 @@ public class Fp {
 "#;
 
 pub const JAVA_LIB_FP2: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import java.math.BigInteger;
 
@@ -181,7 +646,7 @@ public class Fp2 {
 "#;
 
 pub const JAVA_LIB_G1: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import avm.AltBn128;
 
@@ -253,7 +718,7 @@ public class G1 {
 "#;
 
 pub const JAVA_LIB_G1POINT: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import java.math.BigInteger;
 
@@ -304,7 +769,7 @@ public class G1Point {
 "#;
 
 pub const JAVA_LIB_G2: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import java.math.BigInteger;
 import java.util.Arrays;
@@ -546,7 +1011,7 @@ public class G2 {
 "#;
 
 pub const JAVA_LIB_G2POINT: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import java.math.BigInteger;
 
@@ -597,7 +1062,7 @@ public class G2Point {
 "#;
 
 pub const JAVA_LIB_PAIRING: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 import avm.AltBn128;
 
@@ -672,7 +1137,7 @@ public class Pairing {
 "#;
 
 pub const JAVA_LIB_UTIL: &str = r#"// This file is MIT Licensed
-package org.oan.tetryon;
+package <%package%>;
 
 public class Util {
 
@@ -709,3 +1174,134 @@ public class Util {
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_bytes() {
+        let p = G2Affine(
+            ("0x01".to_string(), "0x02".to_string()),
+            ("0x03".to_string(), "0x04".to_string()),
+        );
+        let bytes = java_g2_bytes(&p).unwrap();
+
+        assert_eq!(bytes.len(), 4 * JAVA_ELEMENT_SIZE);
+        assert_eq!((bytes[31], bytes[63], bytes[95], bytes[127]), (2, 1, 4, 3));
+        assert!(bytes.iter().filter(|b| **b != 0).count() == 4);
+
+        let too_large = G1Affine(format!("0x01{:064}", 0), "0x00".to_string());
+        assert!(java_g1_bytes(&too_large).is_err());
+    }
+
+    #[test]
+    fn call_data() {
+        let proof = JavaProof::new(
+            &[vec![1], vec![2]],
+            &["0x0".to_string(), "0xff".to_string()],
+        )
+        .unwrap();
+
+        let method: [u8; 9] = [0x21, 0, 6, b'v', b'e', b'r', b'i', b'f', b'y'];
+        // inputs `0` and `255`, then the proof bytes
+        let arguments: [u8; 16] = [
+            0x31, 0x23, 0, 2, 0x23, 1, 0, 0x23, 2, 0, 0xff, 0x11, 0, 2, 1, 2,
+        ];
+
+        assert_eq!(
+            proof.call_data(None),
+            [&method[..], &arguments[..]].concat()
+        );
+        assert_eq!(
+            proof.call_data(Some(3)),
+            [&method[..], &[0x05, 0, 0, 0, 3][..], &arguments[..]].concat()
+        );
+    }
+
+    #[test]
+    fn test() {
+        let names = JavaNames::new("org.example", "Registry", "My").unwrap();
+        let (name, test) = java_test(&names, 2, &[vec![0x21, 0x0f], vec![0xff]]);
+
+        assert_eq!(name, "RegistryTest.java");
+        assert!(test.contains("public class RegistryTest {"));
+        assert!(test.contains(
+            "avmRule.getDappBytes(Registry.class, null, RegistryCircuit0.class, RegistryCircuit1.class, MyFp.class,"
+        ));
+        assert!(test.contains("private static final String CALL_DATA_0 = \"210f\";"));
+        assert!(test.contains("private static final String CALL_DATA_1 = \"ff\";"));
+        assert!(test.contains("public void tamperedProofFails1() {"));
+        assert!(!test.contains("<%"));
+
+        let (_, test) = java_test(&JavaNames::default(), 1, &[vec![]]);
+        assert!(test.contains("avmRule.getDappBytes(Verifier.class, null, Fp.class,"));
+    }
+
+    #[test]
+    fn validate_names() {
+        assert_eq!(
+            JavaNames::new(JAVA_DEFAULT_PACKAGE, JAVA_DEFAULT_CLASS, ""),
+            Ok(JavaNames::default())
+        );
+        assert!(JavaNames::new("org.example.zk", "MyVerifier", "My").is_ok());
+        assert!(JavaNames::new("org..example", "Verifier", "").is_err());
+        assert!(JavaNames::new("org.example", "1Verifier", "").is_err());
+        assert!(JavaNames::new("org.example", "Verifier", "My-").is_err());
+        assert!(JavaNames::new("org.example", "Pairing", "").is_err());
+        assert!(JavaNames::new("org.example", "Pairing", "My").is_ok());
+    }
+
+    #[test]
+    fn apply() {
+        let names = JavaNames::new("org.example", "MyVerifier", "My").unwrap();
+
+        assert_eq!(
+            names.apply(
+                "package <%package%>;\npublic class <%class%> {\n    G1Point p = G1.add(G1Point.zero(), Fp2.one()); // G1s\n}"
+            ),
+            "package org.example;\npublic class MyVerifier {\n    MyG1Point p = MyG1.add(MyG1Point.zero(), MyFp2.one()); // G1s\n}"
+        );
+        assert_eq!(names.file_name(), "MyVerifier.java");
+    }
+
+    #[test]
+    fn lib() {
+        let names = JavaNames::new("org.example", "MyVerifier", "My").unwrap();
+        let lib = java_lib(&names);
+
+        assert_eq!(lib.len(), 8);
+        assert_eq!(lib[0].0, "MyFp.java");
+        assert!(lib[0].1.contains("package org.example;"));
+        assert!(lib[0].1.contains("public class MyFp {"));
+        assert!(lib.iter().all(|(_, source)| !source.contains("<%")));
+    }
+
+    #[test]
+    fn entry_point() {
+        let template = "public class <%class%> {\n    }\n<%entry_point%>}\n";
+
+        assert!(java_entry_point(template, true).contains("@Callable"));
+        assert_eq!(
+            java_entry_point(template, false),
+            "public class <%class%> {\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let names = JavaNames::new("org.example", "Registry", "My").unwrap();
+        let registry = java_registry(&names, 2);
+
+        assert_eq!(names.circuit(1).file_name(), "RegistryCircuit1.java");
+        assert!(registry.contains("public class Registry {"));
+        assert!(registry.contains("public static final int CIRCUIT_COUNT = 2;"));
+        assert!(registry.contains(
+            "case 0:\n                return RegistryCircuit0.verify(input, RegistryCircuit0.Proof.deserialize(proof));"
+        ));
+        assert!(registry.contains(
+            "case 1:\n                return RegistryCircuit1.verify(input, RegistryCircuit1.Proof.deserialize(proof));"
+        ));
+        assert!(!registry.contains("<%"));
+    }
+}
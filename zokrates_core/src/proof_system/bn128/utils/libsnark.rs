@@ -1,10 +1,57 @@
 use flat_absy::FlatVariable;
 use ir::{self, Statement};
+use proof_system::Error;
 use std::cmp::max;
 use std::collections::HashMap;
+use std::env;
 use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use zokrates_field::field::Field;
 
+/// A temporary directory used to exchange keys and proofs with libsnark, which only works with files.
+/// The directory and its content are removed when it is dropped.
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub fn new() -> Result<Self, Error> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "zokrates-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&path)?;
+
+        Ok(ScratchDir(path))
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn path_as_cstring(path: &Path) -> Result<CString, Error> {
+    let path = path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not valid unicode", path.display()),
+        )
+    })?;
+
+    Ok(CString::new(path).map_err(io::Error::from)?)
+}
+
 // utility function. Converts a Fields vector-based byte representation to fixed size array.
 fn vec_as_u8_32_array(vec: &Vec<u8>) -> [u8; 32] {
     assert!(vec.len() <= 32);
@@ -18,21 +65,24 @@ fn vec_as_u8_32_array(vec: &Vec<u8>) -> [u8; 32] {
 // proof-system-independent preparation for the setup phase
 pub fn prepare_setup<T: Field>(
     program: ir::Prog<T>,
-    pk_path: &str,
-    vk_path: &str,
-) -> (
-    Vec<u8>,
-    Vec<u8>,
-    Vec<u8>,
-    Vec<(i32, i32, [u8; 32])>,
-    Vec<(i32, i32, [u8; 32])>,
-    Vec<(i32, i32, [u8; 32])>,
-    usize,
-    usize,
-    usize,
-    CString,
-    CString,
-) {
+    pk_path: &Path,
+    vk_path: &Path,
+) -> Result<
+    (
+        Vec<u8>,
+        Vec<u8>,
+        Vec<u8>,
+        Vec<(i32, i32, [u8; 32])>,
+        Vec<(i32, i32, [u8; 32])>,
+        Vec<(i32, i32, [u8; 32])>,
+        usize,
+        usize,
+        usize,
+        CString,
+        CString,
+    ),
+    Error,
+> {
     // transform to R1CS
     let (variables, public_variables_count, a, b, c) = r1cs_program(program);
 
@@ -130,10 +180,10 @@ pub fn prepare_setup<T: Field>(
     }
 
     // convert String slices to 'CString's
-    let pk_path_cstring = CString::new(pk_path).unwrap();
-    let vk_path_cstring = CString::new(vk_path).unwrap();
+    let pk_path_cstring = path_as_cstring(pk_path)?;
+    let vk_path_cstring = path_as_cstring(vk_path)?;
 
-    (
+    Ok((
         a_arr,
         b_arr,
         c_arr,
@@ -145,27 +195,38 @@ pub fn prepare_setup<T: Field>(
         num_inputs,
         pk_path_cstring,
         vk_path_cstring,
-    )
+    ))
 }
 
 // proof-system-independent preparation for proof generation
 pub fn prepare_generate_proof<T: Field>(
     program: ir::Prog<T>,
     witness: ir::Witness<T>,
-    pk_path: &str,
-    proof_path: &str,
-) -> (CString, CString, Vec<[u8; 32]>, usize, Vec<[u8; 32]>, usize) {
+    pk_path: &Path,
+    proof_path: &Path,
+) -> Result<(CString, CString, Vec<[u8; 32]>, usize, Vec<[u8; 32]>, usize), Error> {
+    program.check_witness(&witness)?;
+
     // recover variable order from the program
     let (variables, public_variables_count, _, _, _) = r1cs_program(program);
 
-    let witness: Vec<_> = variables.iter().map(|x| witness.0[x].clone()).collect();
+    let witness: Vec<_> = variables
+        .iter()
+        .map(|x| {
+            witness
+                .0
+                .get(x)
+                .cloned()
+                .ok_or_else(|| Error::WitnessMismatch(format!("missing assignment for {}", x)))
+        })
+        .collect::<Result<_, _>>()?;
 
     // split witness into public and private inputs at offset
     let mut public_inputs: Vec<_> = witness.clone();
     let private_inputs: Vec<_> = public_inputs.split_off(public_variables_count);
 
-    let pk_path_cstring = CString::new(pk_path).unwrap();
-    let proof_path_cstring = CString::new(proof_path).unwrap();
+    let pk_path_cstring = path_as_cstring(pk_path)?;
+    let proof_path_cstring = path_as_cstring(proof_path)?;
 
     let public_inputs_length = public_inputs.len();
     let private_inputs_length = private_inputs.len();
@@ -182,14 +243,14 @@ pub fn prepare_generate_proof<T: Field>(
         private_inputs_arr[index] = vec_as_u8_32_array(&value.into_byte_vector());
     }
 
-    (
+    Ok((
         pk_path_cstring,
         proof_path_cstring,
         public_inputs_arr,
         public_inputs_length,
         private_inputs_arr,
         private_inputs_length,
-    )
+    ))
 }
 
 /// Returns the index of `var` in `variables`, adding `var` with incremented index if it not yet exists.
@@ -1,3 +1,21 @@
+use crate::proof_system::bn128::types::{G1Affine, G2Affine};
+
+/// Formats a G1 point as the arguments of a `Pairing.G1Point`
+pub fn solidity_g1(p: &G1Affine) -> String {
+    format!("uint256({}), uint256({})", p.0, p.1)
+}
+
+/// Formats a G2 point as the arguments of a `Pairing.G2Point`
+pub fn solidity_g2(p: &G2Affine) -> String {
+    format!(
+        "[uint256({}), uint256({})], [uint256({}), uint256({})]",
+        (p.0).0,
+        (p.0).1,
+        (p.1).0,
+        (p.1).1
+    )
+}
+
 pub const SOLIDITY_G2_ADDITION_LIB: &str = r#"// This file is LGPL3 Licensed
 
 /**
@@ -1,29 +1,161 @@
 pub mod bn128;
 
-use std::fs::File;
+use std::fmt;
+use std::io;
 use zokrates_field::field::FieldPrime;
 
-pub use self::bn128::G16;
+pub use self::bn128::{G16ProvingKey, G16};
 #[cfg(feature = "libsnark")]
 pub use self::bn128::GM17;
 #[cfg(feature = "libsnark")]
 pub use self::bn128::PGHR13;
 
 use crate::ir;
-use std::io::BufReader;
+use crate::proof_system::bn128::utils::java::{JavaNames, JavaProof};
+use bellman::SynthesisError;
+
+/// The keys produced by a trusted setup
+pub struct SetupKeypair {
+    /// The verification key, serialized as JSON
+    pub vk: String,
+    /// The proving key, in the binary format of the proving scheme
+    pub pk: Vec<u8>,
+}
 
 pub trait ProofSystem {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str);
+    fn setup(&self, program: ir::Prog<FieldPrime>) -> Result<SetupKeypair, Error>;
 
+    /// Generates a proof using the proving key `pk`, and returns it serialized as JSON
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
         witness: ir::Witness<FieldPrime>,
-        pk_path: &str,
-        proof_path: &str,
-    ) -> bool;
+        pk: &[u8],
+    ) -> Result<String, Error>;
+
+    /// Checks a proof against a verification key, using `inputs` as public inputs if provided
+    /// and the inputs embedded in the proof otherwise
+    fn verify(&self, vk: &str, proof: &str, inputs: Option<Vec<FieldPrime>>)
+        -> Result<bool, Error>;
+
+    fn export_solidity_verifier(&self, vk: &str, is_abiv2: bool) -> Result<String, Error>;
+
+    /// Exports a verifier contract for the AVM, with the package and class names set by `names`.
+    /// The `@Callable` entry point is only added if `is_callable` is set, as the verifiers of a
+    /// registry are called through the registry contract
+    fn export_avm_verifier(
+        &self,
+        vk: &str,
+        names: &JavaNames,
+        is_callable: bool,
+    ) -> Result<String, Error>;
+
+    /// Encodes a proof in the layout read by the AVM verifiers
+    fn export_avm_proof(&self, proof: &str) -> Result<JavaProof, Error>;
+}
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>, is_abiv2: bool) -> String;
+#[derive(Debug)]
+pub enum Error {
+    /// A key or proof file could not be read or written
+    Io(io::Error),
+    /// A proving or verification key could not be parsed
+    MalformedKey(String),
+    /// A proof could not be parsed
+    MalformedProof(String),
+    /// The witness does not assign all variables of the program
+    WitnessMismatch(String),
+    /// The witness does not satisfy the constraints of the program
+    UnsatisfiedConstraint {
+        left: String,
+        right: String,
+        location: Option<ir::Location>,
+    },
+    /// A phase 2 ceremony transcript does not check out
+    InvalidCeremony(String),
+    /// A phase 1 file is malformed or its powers are not consistent
+    InvalidPhase1(String),
+    /// The program has more constraints than the phase 1 parameters support
+    CircuitTooLarge {
+        constraints: usize,
+        supported: usize,
+    },
+    /// The proving backend failed for another reason
+    Backend(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::MalformedKey(ref e) => write!(f, "Malformed key: {}", e),
+            Error::MalformedProof(ref e) => write!(f, "Malformed proof: {}", e),
+            Error::WitnessMismatch(ref e) => {
+                write!(f, "Witness does not match the program: {}", e)
+            }
+            Error::UnsatisfiedConstraint {
+                ref left,
+                ref right,
+                ref location,
+            } => {
+                write!(
+                    f,
+                    "Witness does not satisfy the program: expected {} to equal {}",
+                    left, right
+                )?;
+                match location {
+                    Some(location) => write!(f, " {}", location.describe()),
+                    None => Ok(()),
+                }
+            }
+            Error::InvalidCeremony(ref e) => write!(f, "Invalid MPC ceremony: {}", e),
+            Error::InvalidPhase1(ref e) => write!(f, "Invalid phase 1 parameters: {}", e),
+            Error::CircuitTooLarge {
+                constraints,
+                supported,
+            } => write!(
+                f,
+                "Circuit too large: it needs {} constraints (including one per public input), but the phase 1 parameters only support {}",
+                constraints, supported
+            ),
+            Error::Backend(ref e) => write!(f, "Proving backend error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<ir::Error> for Error {
+    fn from(error: ir::Error) -> Self {
+        match error {
+            ir::Error::UnsatisfiedConstraint {
+                left,
+                right,
+                location,
+            } => Error::UnsatisfiedConstraint {
+                left,
+                right,
+                location,
+            },
+            e => Error::WitnessMismatch(e.to_string()),
+        }
+    }
+}
 
-    fn export_avm_verifier(&self, reader: BufReader<File>) -> String;
+impl From<SynthesisError> for Error {
+    fn from(error: SynthesisError) -> Self {
+        match error {
+            SynthesisError::IoError(e) => Error::Io(e),
+            SynthesisError::AssignmentMissing => {
+                Error::WitnessMismatch(SynthesisError::AssignmentMissing.to_string())
+            }
+            SynthesisError::MalformedVerifyingKey => {
+                Error::MalformedKey(SynthesisError::MalformedVerifyingKey.to_string())
+            }
+            e => Error::Backend(e.to_string()),
+        }
+    }
 }
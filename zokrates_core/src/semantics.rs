@@ -19,6 +19,25 @@ use crate::typed_absy::types::{FunctionKey, Signature, Type};
 
 use std::hash::{Hash, Hasher};
 
+/// A marker for the statements checked from `statement` in module `module_id`
+fn location<'ast, T: Field>(
+    module_id: &ModuleId,
+    statement: &StatementNode<'ast, T>,
+) -> TypedStatement<'ast, T> {
+    TypedStatement::Debug(DebugMarker::Location(SourceSpan {
+        module: module_id.clone(),
+        start: statement.start,
+        end: statement.end,
+        // only keep the header of `for` loops, their body has its own locations
+        text: statement
+            .to_string()
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string(),
+    }))
+}
+
 #[derive(PartialEq, Debug)]
 pub struct Error {
     pos: Option<(Position, Position)>,
@@ -591,6 +610,7 @@ impl<'ast> Checker<'ast> {
             Ok(s) => {
                 for stat in funct.statements.into_iter() {
                     let pos = stat.pos();
+                    let location = location(module_id, &stat);
 
                     match self.check_statement(stat, module_id, types) {
                         Ok(statement) => {
@@ -620,6 +640,7 @@ impl<'ast> Checker<'ast> {
                                 }
                                 _ => {}
                             };
+                            statements_checked.push(location);
                             statements_checked.push(statement);
                         }
                         Err(e) => {
@@ -697,7 +718,11 @@ impl<'ast> Checker<'ast> {
             return Err(errors);
         }
 
-        Ok(Signature { inputs, outputs })
+        Ok(Signature {
+            inputs,
+            outputs,
+            parameters: vec![],
+        })
     }
 
     fn check_type(
@@ -844,7 +869,9 @@ impl<'ast> Checker<'ast> {
                 let mut checked_statements = vec![];
 
                 for stat in statements {
+                    let location = location(module_id, &stat);
                     let checked_stat = self.check_statement(stat, module_id, types)?;
+                    checked_statements.push(location);
                     checked_statements.push(checked_stat);
                 }
 
@@ -1886,6 +1913,16 @@ mod tests {
     use typed_absy;
     use zokrates_field::field::FieldPrime;
 
+    /// The location marker of a mocked statement printed as `text` in the module `""`
+    fn mock_location<'ast>(text: &str) -> TypedStatement<'ast, FieldPrime> {
+        TypedStatement::Debug(DebugMarker::Location(SourceSpan {
+            module: String::from(""),
+            start: Position::mock(),
+            end: Position::mock(),
+            text: String::from(text),
+        }))
+    }
+
     const MODULE_ID: &str = "";
 
     mod array {
@@ -2674,19 +2711,24 @@ mod tests {
         .mock()];
 
         let for_statements_checked = vec![
+            mock_location("field a"),
             TypedStatement::Declaration(typed_absy::Variable::field_element("a".into())),
+            mock_location("a = i"),
             TypedStatement::Definition(
                 TypedAssignee::Identifier(typed_absy::Variable::field_element("a".into())),
                 FieldElementExpression::Identifier("i".into()).into(),
             ),
         ];
 
-        let foo_statements_checked = vec![TypedStatement::For(
-            typed_absy::Variable::field_element("i".into()),
-            FieldPrime::from(0),
-            FieldPrime::from(10),
-            for_statements_checked,
-        )];
+        let foo_statements_checked = vec![
+            mock_location("for field i in 0..10 do"),
+            TypedStatement::For(
+                typed_absy::Variable::field_element("i".into()),
+                FieldPrime::from(0),
+                FieldPrime::from(10),
+                for_statements_checked,
+            ),
+        ];
 
         let foo = Function {
             arguments: vec![],
@@ -2704,6 +2746,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -2741,6 +2784,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement, Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -2789,6 +2833,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement, Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -3080,8 +3125,11 @@ mod tests {
         ];
 
         let bar_statements_checked: Vec<TypedStatement<FieldPrime>> = vec![
+            mock_location("field a"),
             TypedStatement::Declaration(typed_absy::Variable::field_element("a".into())),
+            mock_location("field b"),
             TypedStatement::Declaration(typed_absy::Variable::field_element("b".into())),
+            mock_location("a, b = foo()"),
             TypedStatement::MultipleDefinition(
                 vec![
                     typed_absy::Variable::field_element("a".into()),
@@ -3095,6 +3143,7 @@ mod tests {
                     vec![Type::FieldElement, Type::FieldElement],
                 ),
             ),
+            mock_location("return (a + b)"),
             TypedStatement::Return(vec![FieldElementExpression::Add(
                 box FieldElementExpression::Identifier("a".into()),
                 box FieldElementExpression::Identifier("b".into()),
@@ -3107,6 +3156,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement, Type::FieldElement],
+                parameters: vec![],
             },
         };
 
@@ -3129,6 +3179,7 @@ mod tests {
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
+                parameters: vec![],
             },
         };
 
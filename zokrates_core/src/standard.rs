@@ -82,6 +82,7 @@ pub fn sha_round<T: Field>() -> FlatFunction<T> {
             Type::array(Type::FieldElement, current_hash_indices.len()),
         ],
         outputs: vec![Type::array(Type::FieldElement, output_indices.len())],
+        parameters: vec![],
     };
 
     // define parameters to the function based on the variables
@@ -82,6 +82,7 @@ impl<T: Field> FlatStatement<T> {
                     .collect(),
                 ..d
             })),
+            FlatStatement::Debug(marker) => Some(FlatStatement::Debug(marker)),
         }
     }
 }
@@ -129,6 +129,12 @@ impl<'ast, T: Field> Inliner<'ast, T> {
 
                 self.statement_buffer.extend(inputs_bindings);
 
+                // mark the inlined body, so that its statements can be traced back to this call
+                self.statement_buffer
+                    .push(TypedStatement::Debug(DebugMarker::EnterCall(
+                        key.id.to_string(),
+                    )));
+
                 // filter out the return statement and keep it aside
                 let (statements, mut ret): (Vec<_>, Vec<_>) = function
                     .statements
@@ -141,6 +147,8 @@ impl<'ast, T: Field> Inliner<'ast, T> {
 
                 // add all statements to the buffer
                 self.statement_buffer.extend(statements);
+                self.statement_buffer
+                    .push(TypedStatement::Debug(DebugMarker::ExitCall));
 
                 // pop this call from the stack
                 self.stack.pop();
@@ -381,9 +389,13 @@ mod tests {
                 .unwrap(),
             &TypedFunctionSymbol::Here(TypedFunction {
                 arguments: vec![],
-                statements: vec![TypedStatement::Return(vec![
-                    FieldElementExpression::Number(FieldPrime::from(42)).into(),
-                ])],
+                statements: vec![
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("foo"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
+                    TypedStatement::Return(vec![
+                        FieldElementExpression::Number(FieldPrime::from(42)).into(),
+                    ]),
+                ],
                 signature: Signature::new().outputs(vec![Type::FieldElement]),
             })
         );
@@ -524,6 +536,8 @@ mod tests {
                         )),
                         FieldElementExpression::Identifier("a".into()).into()
                     ),
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("foo"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
                     TypedStatement::Return(vec![FieldElementExpression::Mult(
                         box FieldElementExpression::Identifier("a".into()),
                         box FieldElementExpression::Mult(
@@ -642,6 +656,8 @@ mod tests {
             &TypedFunctionSymbol::Here(TypedFunction {
                 arguments: vec![],
                 statements: vec![
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("foo"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
                     TypedStatement::Definition(
                         TypedAssignee::Identifier(Variable::field_element("a".into())),
                         FieldElementExpression::Number(FieldPrime::from(42)).into()
@@ -735,6 +751,8 @@ mod tests {
             &TypedFunctionSymbol::Here(TypedFunction {
                 arguments: vec![],
                 statements: vec![
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("foo"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
                     TypedStatement::Definition(
                         TypedAssignee::Identifier(Variable::field_element("a".into())),
                         FieldElementExpression::Number(FieldPrime::from(42)).into()
@@ -893,6 +911,8 @@ mod tests {
                         )),
                         FieldElementExpression::Identifier("a".into()).into()
                     ),
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("main"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
                     TypedStatement::Definition(
                         TypedAssignee::Identifier(Variable::field_element(
                             Identifier::from("a").stack(stack1.clone())
@@ -902,6 +922,8 @@ mod tests {
                         )
                         .into()
                     ),
+                    TypedStatement::Debug(DebugMarker::EnterCall(String::from("main"))),
+                    TypedStatement::Debug(DebugMarker::ExitCall),
                     TypedStatement::Return(vec![FieldElementExpression::Identifier(
                         Identifier::from("a").stack(stack1.clone())
                     )
@@ -97,6 +97,7 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                     expression_list,
                 ))
             }
+            TypedStatement::Debug(marker) => Some(TypedStatement::Debug(marker)),
         };
         match res {
             Some(v) => vec![v],
@@ -0,0 +1,174 @@
+//! The interface of a compiled program, written next to it by `zokrates compile` as `abi.json`
+//! so that tools can build inputs for `main` and read its outputs without parsing the source.
+
+use crate::typed_absy::types::{Signature, SignatureParameter, Type};
+
+/// A parameter of `main`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AbiInput {
+    pub name: String,
+    pub public: bool,
+    #[serde(flatten, with = "abi_type")]
+    pub ty: Type,
+}
+
+/// A return value of `main`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AbiOutput {
+    #[serde(flatten, with = "abi_type")]
+    pub ty: Type,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Abi {
+    pub inputs: Vec<AbiInput>,
+    pub outputs: Vec<AbiOutput>,
+}
+
+impl Abi {
+    pub fn signature(&self) -> Signature {
+        Signature::new()
+            .inputs(self.inputs.iter().map(|i| i.ty.clone()).collect())
+            .outputs(self.outputs.iter().map(|o| o.ty.clone()).collect())
+            .parameters(
+                self.inputs
+                    .iter()
+                    .map(|i| SignatureParameter {
+                        name: i.name.clone(),
+                        public: i.public,
+                    })
+                    .collect(),
+            )
+    }
+}
+
+/// JSON representation of a `Type` in the ABI, where each type is an object tagged with its kind:
+/// `{"type": "field"}`, `{"type": "bool"}`, `{"type": "array", "size": 2, "components": {..}}`
+/// or `{"type": "struct", "components": [{"name": "a", ..}]}`
+mod abi_type {
+    use super::Type;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    enum AbiType {
+        Field,
+        Bool,
+        Array {
+            size: usize,
+            components: Box<AbiType>,
+        },
+        Struct {
+            components: Vec<AbiMember>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct AbiMember {
+        name: String,
+        #[serde(flatten)]
+        ty: AbiType,
+    }
+
+    impl From<Type> for AbiType {
+        fn from(t: Type) -> AbiType {
+            match t {
+                Type::FieldElement => AbiType::Field,
+                Type::Boolean => AbiType::Bool,
+                Type::Array(box ty, size) => AbiType::Array {
+                    size,
+                    components: box ty.into(),
+                },
+                Type::Struct(members) => AbiType::Struct {
+                    components: members
+                        .into_iter()
+                        .map(|(name, ty)| AbiMember {
+                            name,
+                            ty: ty.into(),
+                        })
+                        .collect(),
+                },
+            }
+        }
+    }
+
+    impl From<AbiType> for Type {
+        fn from(t: AbiType) -> Type {
+            match t {
+                AbiType::Field => Type::FieldElement,
+                AbiType::Bool => Type::Boolean,
+                AbiType::Array {
+                    size,
+                    components: box ty,
+                } => Type::array(ty.into(), size),
+                AbiType::Struct { components } => Type::Struct(
+                    components
+                        .into_iter()
+                        .map(|m| (m.name, m.ty.into()))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(ty: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+        AbiType::from(ty.clone()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+        AbiType::deserialize(deserializer).map(Type::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: String::from("a"),
+                    public: true,
+                    ty: Type::FieldElement,
+                },
+                AbiInput {
+                    name: String::from("b"),
+                    public: false,
+                    ty: Type::Struct(vec![(String::from("c"), Type::array(Type::Boolean, 2))]),
+                },
+            ],
+            outputs: vec![AbiOutput {
+                ty: Type::FieldElement,
+            }],
+        };
+
+        let json = serde_json::to_string(&abi).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"inputs":[{"name":"a","public":true,"type":"field"},{"name":"b","public":false,"type":"struct","components":[{"name":"c","type":"array","size":2,"components":{"type":"bool"}}]}],"outputs":[{"type":"field"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Abi>(&json).unwrap(), abi);
+        assert_eq!(
+            abi.signature(),
+            Signature::new()
+                .inputs(vec![
+                    Type::FieldElement,
+                    Type::Struct(vec![(String::from("c"), Type::array(Type::Boolean, 2))])
+                ])
+                .outputs(vec![Type::FieldElement])
+                .parameters(vec![
+                    SignatureParameter {
+                        name: String::from("a"),
+                        public: true
+                    },
+                    SignatureParameter {
+                        name: String::from("b"),
+                        public: false
+                    }
+                ])
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Source locations carried through the compilation pipeline, so that the statements of the IR can
+//! be traced back to the source code they were compiled from.
+//!
+//! Positions are dropped when checking the program, so instead of attaching a position to every
+//! statement, `DebugMarker`s are interleaved with statements in `typed_absy` and `flat_absy`:
+//! each statement comes from the span set by the last `Location` marker before it.
+
+use crate::parser::Position;
+use std::fmt;
+
+/// A span of source code in a module
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceSpan {
+    /// The id of the module, as used to report errors
+    pub module: String,
+    pub start: Position,
+    pub end: Position,
+    /// The statement found at this span, printed back on a single line
+    pub text: String,
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.module, self.start)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DebugMarker {
+    /// The following statements come from this span
+    Location(SourceSpan),
+    /// The following statements, up to the matching `ExitCall`, are the body of the function with
+    /// this name, which was inlined at the current location
+    EnterCall(String),
+    /// The end of an inlined function body. The location of the call applies again
+    ExitCall,
+}
+
+impl fmt::Display for DebugMarker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DebugMarker::Location(ref span) => write!(f, "// {}", span),
+            DebugMarker::EnterCall(ref function) => write!(f, "// enter call to {}", function),
+            DebugMarker::ExitCall => write!(f, "// exit call"),
+        }
+    }
+}
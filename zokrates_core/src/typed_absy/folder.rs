@@ -168,6 +168,7 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
             variables.into_iter().map(|v| f.fold_variable(v)).collect(),
             f.fold_expression_list(elist),
         ),
+        TypedStatement::Debug(marker) => TypedStatement::Debug(marker),
     };
     vec![res]
 }
@@ -5,11 +5,15 @@
 //! @author Jacob Eberhardt <jacob.eberhardt@tu-berlin.de>
 //! @date 2017
 
+pub mod abi;
+pub mod debug;
 pub mod folder;
 mod parameter;
 pub mod types;
 mod variable;
 
+pub use crate::typed_absy::abi::{Abi, AbiInput, AbiOutput};
+pub use crate::typed_absy::debug::{DebugMarker, SourceSpan};
 pub use crate::typed_absy::parameter::Parameter;
 pub use crate::typed_absy::types::Type;
 pub use crate::typed_absy::variable::Variable;
@@ -53,6 +57,42 @@ pub struct TypedProgram<'ast, T: Field> {
     pub main: TypedModuleId,
 }
 
+impl<'ast, T: Field> TypedProgram<'ast, T> {
+    /// Returns the interface of the `main` function of the main module
+    pub fn abi(&self) -> Abi {
+        let main = self
+            .modules
+            .get(&self.main)
+            .unwrap()
+            .functions
+            .iter()
+            .find(|(k, _)| k.id == "main")
+            .unwrap()
+            .1;
+
+        match main {
+            TypedFunctionSymbol::Here(f) => Abi {
+                inputs: f
+                    .arguments
+                    .iter()
+                    .map(|p| AbiInput {
+                        name: p.id.id.id.to_string(),
+                        public: !p.private,
+                        ty: p.id.get_type(),
+                    })
+                    .collect(),
+                outputs: f
+                    .signature
+                    .outputs
+                    .iter()
+                    .map(|ty| AbiOutput { ty: ty.clone() })
+                    .collect(),
+            },
+            _ => unreachable!("main should be a typed function locally"),
+        }
+    }
+}
+
 impl<'ast, T: Field> fmt::Display for TypedProgram<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for (module_id, module) in &self.modules {
@@ -294,6 +334,7 @@ pub enum TypedStatement<'ast, T: Field> {
     Condition(TypedExpression<'ast, T>, TypedExpression<'ast, T>),
     For(Variable<'ast>, T, T, Vec<TypedStatement<'ast, T>>),
     MultipleDefinition(Vec<Variable<'ast>>, TypedExpressionList<'ast, T>),
+    Debug(DebugMarker),
 }
 
 impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
@@ -326,6 +367,7 @@ impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
             TypedStatement::MultipleDefinition(ref lhs, ref rhs) => {
                 write!(f, "MultipleDefinition({:?}, {:?})", lhs, rhs)
             }
+            TypedStatement::Debug(ref marker) => write!(f, "Debug({:?})", marker),
         }
     }
 }
@@ -362,6 +404,7 @@ impl<'ast, T: Field> fmt::Display for TypedStatement<'ast, T> {
                 }
                 write!(f, " = {}", rhs)
             }
+            TypedStatement::Debug(ref marker) => write!(f, "{}", marker),
         }
     }
 }
@@ -113,16 +113,26 @@ impl<'ast> FunctionKey<'ast> {
     }
 }
 
-pub use self::signature::Signature;
+pub use self::signature::{Signature, SignatureParameter};
 
 pub mod signature {
     use super::*;
     use std::fmt;
 
+    /// The name and visibility of a source-level parameter
+    #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Ord, PartialOrd, Debug)]
+    pub struct SignatureParameter {
+        pub name: String,
+        pub public: bool,
+    }
+
     #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Ord, PartialOrd)]
     pub struct Signature {
         pub inputs: Vec<Type>,
         pub outputs: Vec<Type>,
+        /// The parameters matching `inputs`. Only kept for the `main` function of a compiled
+        /// program, and empty otherwise so that they do not affect function resolution
+        pub parameters: Vec<SignatureParameter>,
     }
 
     impl fmt::Debug for Signature {
@@ -203,6 +213,7 @@ pub mod signature {
             Signature {
                 inputs: vec![],
                 outputs: vec![],
+                parameters: vec![],
             }
         }
 
@@ -215,6 +226,11 @@ pub mod signature {
             self.outputs = outputs;
             self
         }
+
+        pub fn parameters(mut self, parameters: Vec<SignatureParameter>) -> Self {
+            self.parameters = parameters;
+            self
+        }
     }
 
     #[cfg(test)]
@@ -36,7 +36,26 @@ type Val = String;
 
 impl From<ir::ExecutionResult<FieldPrime>> for ComparableResult {
     fn from(r: ir::ExecutionResult<FieldPrime>) -> ComparableResult {
-        ComparableResult(r.map(|v| v.return_values()))
+        ComparableResult(r.map(|v| v.return_values()).map_err(without_location))
+    }
+}
+
+// expected errors do not specify where they happen in the source
+fn without_location(e: ir::Error) -> ir::Error {
+    match e {
+        ir::Error::UnsatisfiedConstraint { left, right, .. } => ir::Error::UnsatisfiedConstraint {
+            left,
+            right,
+            location: None,
+        },
+        ir::Error::Solver {
+            helper, message, ..
+        } => ir::Error::Solver {
+            helper,
+            message,
+            location: None,
+        },
+        e => e,
     }
 }
 
@@ -68,7 +87,7 @@ fn compare(result: ir::ExecutionResult<FieldPrime>, expected: TestResult) -> Res
 }
 
 use std::io::{BufReader, Read};
-use zokrates_core::compile::compile;
+use zokrates_core::compile::{compile, CompilationArtifacts};
 use zokrates_fs_resolver::resolve;
 
 pub fn test_inner(test_path: &str) {
@@ -77,7 +96,7 @@ pub fn test_inner(test_path: &str) {
 
     let mut code_reader = BufReader::new(File::open(&t.entry_point).unwrap());
 
-    let bin = compile(
+    let artifacts: CompilationArtifacts<FieldPrime> = compile(
         &mut code_reader,
         Some(
             t.entry_point
@@ -91,6 +110,8 @@ pub fn test_inner(test_path: &str) {
     )
     .unwrap();
 
+    let bin = artifacts.prog();
+
     for test in t.tests.into_iter() {
         let input = &test.input.values;
         let output = bin.execute(